@@ -1,29 +1,276 @@
-use serde::{ser::SerializeStruct, Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{
+    de::{self, Visitor},
+    ser::{SerializeMap, SerializeStruct},
+    Deserialize, Deserializer, Serialize,
+};
 use serde_with::{serde_as, DefaultOnError};
 use std::fmt;
 
+use super::app_errors::AppError;
+
+// Creation and modification times are only known when the API returns them. They are never
+// written, so exports, bundles and request bodies stay the same.
+pub trait Timestamped {
+    // The modification time, or the creation time of an item never modified
+    fn get_modified_at(&self) -> Option<DateTime<Utc>>;
+}
+
+struct ModbusAddressVisitor;
+
+impl Visitor<'_> for ModbusAddressVisitor {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a decimal Modbus address or a hex one starting with 0x")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<usize, E> {
+        usize::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<usize, E> {
+        usize::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<usize, E> {
+        let v = v.trim();
+        let parsed = match v.strip_prefix("0x").or_else(|| v.strip_prefix("0X")) {
+            Some(hex) => usize::from_str_radix(hex, 16),
+            None => v.parse::<usize>(),
+        };
+
+        parsed.map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+// Modbus documentation often gives addresses in hex, e.g. 0x10 for 16
+fn deserialize_modbus_address<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<usize, D::Error> {
+    deserializer.deserialize_any(ModbusAddressVisitor)
+}
+
+pub const MODBUS_REGISTER_TYPES: [&str; 4] =
+    ["holdingRegister", "inputRegister", "coil", "discreteInput"];
+
+pub const MODBUS_DATA_TYPES: [&str; 9] = [
+    "uInteger16",
+    "integer16",
+    "uInteger32BigEndian",
+    "uInteger32LittleEndian",
+    "integer32BigEndian",
+    "integer32LittleEndian",
+    "float32BigEndian",
+    "float32LittleEndian",
+    "boolean",
+];
+
 // marker trait
 pub trait NumericSensor {
     fn clean_sensor_empty_unit(&mut self);
+
+    // A multiplier of 0 or NaN turns every reading into 0 or NaN
+    fn check_multiplier(&self) -> Result<(), AppError>;
+
+    // Shared by import and validation, such multipliers are only rejected when strict
+    fn check_multiplier_strict(&self, strict: bool) -> Result<(), AppError> {
+        match self.check_multiplier() {
+            Err(e) if strict => Err(e),
+            Err(e) => {
+                warn!("{}", e);
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+}
+
+fn check_multiplier(name: &str, multiplier: f64) -> Result<(), AppError> {
+    if multiplier == 0.0 || multiplier.is_nan() {
+        Err(AppError::InvalidMultiplier(name.to_string(), multiplier))
+    } else {
+        Ok(())
+    }
 }
 
 pub trait GenericSensor {
     fn get_id_as_string(&self) -> String;
+    fn get_name(&self) -> String;
     fn clean_empty_id(&mut self);
+    fn clear_id(&mut self);
+
+    // Identifies the point a sensor reads, two sensors in one definition should never share it
+    fn get_point_key(&self) -> String;
+
+    // Object and sensor type, searched by search-sensors --match-types
+    fn get_type_names(&self) -> Vec<String>;
+
+    // Used to find the existing sensor a row without an id refers to
+    fn is_same_sensor(&self, other: &Self) -> bool {
+        self.get_name() == other.get_name()
+    }
 }
 
-#[derive(Debug)]
+// Builds a sensor from a CSV row, value mappings are parsed with the given separators
+pub trait FromSensorCsv<C>: Sized {
+    fn from_csv(source: C, separators: &MappingSeparators) -> Result<Self, AppError>;
+}
+
+// Standard object types from ASHRAE 135, in the form the API expects
+pub const BACNET_OBJECT_TYPES: [&str; 64] = [
+    "accessCredential",
+    "accessDoor",
+    "accessPoint",
+    "accessRights",
+    "accessUser",
+    "accessZone",
+    "accumulator",
+    "alertEnrollment",
+    "analogInput",
+    "analogOutput",
+    "analogValue",
+    "auditLog",
+    "auditReporter",
+    "averaging",
+    "binaryInput",
+    "binaryLightingOutput",
+    "binaryOutput",
+    "binaryValue",
+    "bitstringValue",
+    "calendar",
+    "channel",
+    "characterstringValue",
+    "color",
+    "colorTemperature",
+    "command",
+    "credentialDataInput",
+    "datePatternValue",
+    "dateValue",
+    "datetimePatternValue",
+    "datetimeValue",
+    "device",
+    "elevatorGroup",
+    "escalator",
+    "eventEnrollment",
+    "eventLog",
+    "file",
+    "globalGroup",
+    "group",
+    "integerValue",
+    "largeAnalogValue",
+    "lifeSafetyPoint",
+    "lifeSafetyZone",
+    "lift",
+    "lightingOutput",
+    "loadControl",
+    "loop",
+    "multiStateInput",
+    "multiStateOutput",
+    "multiStateValue",
+    "networkPort",
+    "networkSecurity",
+    "notificationClass",
+    "notificationForwarder",
+    "octetstringValue",
+    "positiveIntegerValue",
+    "program",
+    "pulseConverter",
+    "schedule",
+    "staging",
+    "structuredView",
+    "timePatternValue",
+    "timeValue",
+    "timer",
+    "trendLog",
+];
+
+pub trait BacnetSensor {
+    // Replaces the object type with the exact form the API expects
+    fn normalize_object_type(&mut self) -> Result<(), AppError>;
+}
+
+pub fn normalize_object_type(object_type: &str) -> Result<&'static str, AppError> {
+    find_ignore_case(object_type, &BACNET_OBJECT_TYPES).ok_or_else(|| {
+        AppError::InvalidObjectType(object_type.to_string(), BACNET_OBJECT_TYPES.join(", "))
+    })
+}
+
+pub trait ModbusSensor {
+    // Replaces the register and data type with the exact form the API expects
+    fn normalize_modbus_types(&mut self) -> Result<(), AppError>;
+}
+
+fn find_ignore_case(value: &str, valid_values: &[&'static str]) -> Option<&'static str> {
+    valid_values
+        .iter()
+        .find(|v| v.eq_ignore_ascii_case(value.trim()))
+        .copied()
+}
+
+pub fn normalize_register_type(register_type: &str) -> Result<&'static str, AppError> {
+    find_ignore_case(register_type, &MODBUS_REGISTER_TYPES).ok_or_else(|| {
+        AppError::InvalidRegisterType(register_type.to_string(), MODBUS_REGISTER_TYPES.join(", "))
+    })
+}
+
+pub fn normalize_data_type(data_type: &str) -> Result<&'static str, AppError> {
+    find_ignore_case(data_type, &MODBUS_DATA_TYPES).ok_or_else(|| {
+        AppError::InvalidDataType(data_type.to_string(), MODBUS_DATA_TYPES.join(", "))
+    })
+}
+
+// Register width in bits for a Modbus data type, None when the data type is not known, which only
+// gets this far in lenient mode. Booleans are read from a single 16 bit register.
+pub fn get_data_type_width(data_type: &str) -> Option<usize> {
+    match find_ignore_case(data_type, &MODBUS_DATA_TYPES)? {
+        "uInteger16" | "integer16" | "boolean" => Some(16),
+        "uInteger32BigEndian"
+        | "uInteger32LittleEndian"
+        | "integer32BigEndian"
+        | "integer32LittleEndian"
+        | "float32BigEndian"
+        | "float32LittleEndian" => Some(32),
+        _ => None,
+    }
+}
+
+// Bits are numbered from 0, e.g. 0-15 on a 16 bit register. A range starting at 1 or above may use
+// 1 based numbering instead, so its end bit may equal the register width.
+pub fn validate_bit_range(
+    start_bit: usize,
+    end_bit: usize,
+    data_type: &str,
+) -> Result<(), AppError> {
+    let width = get_data_type_width(data_type);
+    let last_bit = width.map(|w| if start_bit == 0 { w - 1 } else { w });
+
+    if start_bit > end_bit || last_bit.is_some_and(|l| end_bit > l) {
+        Err(AppError::InvalidBitRange(
+            start_bit,
+            end_bit,
+            data_type.to_string(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DefinitionType {
     Bacnet,
     Modbus,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum DefinitionDataType {
     Numeric,
     NonNumeric,
 }
 
+#[serde_as]
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Definition {
@@ -31,8 +278,131 @@ pub struct Definition {
     pub name: String,
     #[serde(alias = "assetType")]
     pub asset_type: String,
-    #[serde(alias = "associatedAssets")]
+    #[serde(alias = "associatedAssets", default)]
     pub associated_assets: usize,
+    #[serde(skip)]
+    pub sensor_counts: Option<SensorCounts>,
+    #[serde(default, skip_serializing)]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing, alias = "lastModified")]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl Timestamped for Definition {
+    fn get_modified_at(&self) -> Option<DateTime<Utc>> {
+        self.modified_at.or(self.created_at)
+    }
+}
+
+// A sensor of any class in its CSV form, with the definition it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinitionSensor {
+    pub definition_id: String,
+    pub sensor_class: String,
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl DefinitionSensor {
+    pub fn get(&self, column: &str) -> Option<String> {
+        match column {
+            "definitionId" => Some(self.definition_id.clone()),
+            "sensorClass" => Some(self.sensor_class.clone()),
+            _ => self.fields.get(column).map(|v| match v {
+                serde_json::Value::Null => String::new(),
+                serde_json::Value::String(s) => s.clone(),
+                v => v.to_string(),
+            }),
+        }
+    }
+}
+
+impl Serialize for DefinitionSensor {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.fields.len() + 2))?;
+
+        map.serialize_entry("definitionId", &self.definition_id)?;
+        map.serialize_entry("sensorClass", &self.sensor_class)?;
+
+        for (k, v) in &self.fields {
+            map.serialize_entry(k, v)?;
+        }
+
+        map.end()
+    }
+}
+
+// A listed item reduced to the columns given with --columns, in the order they were given
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct SelectedColumns(pub serde_json::Map<String, serde_json::Value>);
+
+impl SelectedColumns {
+    pub fn get(&self, column: &str) -> String {
+        match self.0.get(column) {
+            None | Some(serde_json::Value::Null) => String::new(),
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(v) => v.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SelectedColumns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines = self
+            .0
+            .keys()
+            .map(|k| format!("{}: {}", k, self.get(k)))
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+// Paged sensor lists are either a bare list or the items next to the paging metadata
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SensorPage<T> {
+    Items(Vec<T>),
+    Wrapped {
+        data: Vec<T>,
+        #[serde(alias = "totalCount")]
+        total: Option<usize>,
+    },
+}
+
+impl<T> SensorPage<T> {
+    pub fn into_parts(self) -> (Vec<T>, Option<usize>) {
+        match self {
+            SensorPage::Items(items) => (items, None),
+            SensorPage::Wrapped { data, total } => (data, total),
+        }
+    }
+}
+
+// Increased when the bundle layout changes in a way older versions can't import
+pub const BUNDLE_VERSION: u32 = 1;
+
+// A definition with all its sensors, sensors are kept in the form the API uses
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefinitionBundle {
+    pub version: u32,
+    pub definition_type: DefinitionType,
+    pub definition: Definition,
+    pub numeric_sensors: Vec<serde_json::Value>,
+    pub non_numeric_sensors: Vec<serde_json::Value>,
+}
+
+// A count is None when its sensors could not be fetched
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SensorCounts {
+    pub numeric: Option<usize>,
+    pub non_numeric: Option<usize>,
 }
 
 impl fmt::Display for Definition {
@@ -46,7 +416,23 @@ impl fmt::Display for Definition {
             f,
             "id: {}\nname: {}\nasset type: {}\nassociated_assets: {}",
             id, self.name, self.asset_type, self.associated_assets
-        )
+        )?;
+
+        if let Some(counts) = &self.sensor_counts {
+            let format_count = |count: Option<usize>| match count {
+                Some(c) => c.to_string(),
+                None => "unavailable".to_string(),
+            };
+
+            write!(
+                f,
+                "\nnumeric sensors: {}\nnon-numeric sensors: {}",
+                format_count(counts.numeric),
+                format_count(counts.non_numeric)
+            )?;
+        }
+
+        Ok(())
     }
 }
 
@@ -56,20 +442,32 @@ impl fmt::Display for Definition {
 pub struct BacnetIpNumericSensor {
     pub id: Option<String>,
     pub name: String,
-    multiplier: f64,
+    pub multiplier: f64,
     #[serde(alias = "objectInstance")]
-    object_instance: usize,
+    pub object_instance: usize,
     #[serde(alias = "objectType")]
-    object_type: String,
+    pub object_type: String,
     #[serde(alias = "sensorType")]
-    sensor_type: String,
+    pub sensor_type: String,
     #[serde(alias = "sensorTypeId")]
-    sensor_type_id: String,
+    pub sensor_type_id: String,
     #[serde_as(deserialize_as = "DefaultOnError")]
     pub unit: Option<String>,
     #[serde(alias = "unitId")]
     #[serde_as(deserialize_as = "DefaultOnError")]
     pub unit_id: Option<String>,
+    #[serde(default, skip_serializing)]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing, alias = "lastModified")]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl Timestamped for BacnetIpNumericSensor {
+    fn get_modified_at(&self) -> Option<DateTime<Utc>> {
+        self.modified_at.or(self.created_at)
+    }
 }
 
 impl fmt::Display for BacnetIpNumericSensor {
@@ -97,6 +495,14 @@ impl fmt::Display for BacnetIpNumericSensor {
     }
 }
 
+impl BacnetSensor for BacnetIpNumericSensor {
+    fn normalize_object_type(&mut self) -> Result<(), AppError> {
+        self.object_type = normalize_object_type(&self.object_type)?.to_string();
+
+        Ok(())
+    }
+}
+
 impl GenericSensor for BacnetIpNumericSensor {
     fn get_id_as_string(&self) -> String {
         match self.id.clone() {
@@ -105,12 +511,34 @@ impl GenericSensor for BacnetIpNumericSensor {
         }
     }
 
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_point_key(&self) -> String {
+        format!("{} {}", self.object_type, self.object_instance)
+    }
+
+    fn get_type_names(&self) -> Vec<String> {
+        vec![self.object_type.clone(), self.sensor_type.clone()]
+    }
+
     fn clean_empty_id(&mut self) {
         let id = self.get_id_as_string();
         if String::is_empty(&id) {
             self.id = None;
         }
     }
+
+    fn clear_id(&mut self) {
+        self.id = None;
+    }
+
+    fn is_same_sensor(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.object_instance == other.object_instance
+            && self.object_type == other.object_type
+    }
 }
 
 impl NumericSensor for BacnetIpNumericSensor {
@@ -123,51 +551,220 @@ impl NumericSensor for BacnetIpNumericSensor {
             self.unit = None;
         }
     }
+
+    fn check_multiplier(&self) -> Result<(), AppError> {
+        check_multiplier(&self.name, self.multiplier)
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ValueMapping {
-    text: String,
-    value: usize,
+// Numeric sensors have no value mappings, their CSV rows are read as the sensor itself
+impl FromSensorCsv<BacnetIpNumericSensor> for BacnetIpNumericSensor {
+    fn from_csv(
+        source: BacnetIpNumericSensor,
+        _separators: &MappingSeparators,
+    ) -> Result<Self, AppError> {
+        Ok(source)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueMapping {
+    pub text: String,
+    pub value: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 impl fmt::Display for ValueMapping {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "text: {}, value: {}", self.text, self.value)
+        write!(f, "text: {}, value: {}", self.text, self.value)?;
+
+        if let Some(description) = &self.description {
+            write!(f, ", description: {}", description)?;
+        }
+
+        Ok(())
     }
 }
 
+// Separators between value mapping pairs and between the parts of a pair, set from the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MappingSeparators {
+    pub pair: char,
+    pub kv: char,
+}
+
+impl Default for MappingSeparators {
+    fn default() -> Self {
+        MappingSeparators { pair: ',', kv: ':' }
+    }
+}
+
+fn escape_value_mapping_text(text: &str, separators: &MappingSeparators) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if c == '\\' || c == separators.pair || c == separators.kv {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+// Value mappings are encoded as comma separated text:value pairs by default, e.g.
+// "Inactive:0,Active:1", with an optional description as a third part, e.g. "Fault:2:Check the belt".
+// Separators and backslashes in the text are escaped with a backslash, e.g. "On\, manual:1".
+pub fn format_value_mapping_with(
+    value_mapping: &[ValueMapping],
+    separators: &MappingSeparators,
+) -> String {
+    let kv = separators.kv;
+
+    value_mapping
+        .iter()
+        .map(|vm| {
+            let text = escape_value_mapping_text(&vm.text, separators);
+
+            match vm.description.as_deref() {
+                Some(description) if !description.is_empty() => format!(
+                    "{}{kv}{}{kv}{}",
+                    text,
+                    vm.value,
+                    escape_value_mapping_text(description, separators)
+                ),
+                _ => format!("{}{kv}{}", text, vm.value),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(&separators.pair.to_string())
+}
+
+// Splits on unescaped pair separators, shown here with the default comma and colon.
+// A pair whose last unescaped colon is followed by a number is
+// text:value, since the value never contains a colon. Otherwise the first unescaped colon
+// followed by a number and another colon starts the value, and the rest is the description.
+// Empty pairs are ignored, while a pair without a colon or ending in a lone backslash is an error.
+pub fn parse_value_mapping_with(
+    value_mapping: &str,
+    separators: &MappingSeparators,
+) -> Result<Vec<ValueMapping>, AppError> {
+    let mut pairs = Vec::new();
+    let mut pair = (String::new(), Vec::new());
+    let mut chars = value_mapping.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                pair.0.push(c);
+
+                match chars.next() {
+                    Some(escaped) => {
+                        pair.0.push(escaped);
+                        pair.1.push((escaped, true));
+                    }
+                    None => return Err(AppError::InvalidValueMapping(pair.0)),
+                }
+            }
+            c if c == separators.pair => pairs.push(std::mem::take(&mut pair)),
+            _ => {
+                pair.0.push(c);
+                pair.1.push((c, false));
+            }
+        }
+    }
+
+    pairs.push(pair);
+
+    pairs
+        .into_iter()
+        .filter(|(raw, _)| !raw.trim().is_empty())
+        .map(|(raw, chars)| {
+            let colons = chars
+                .iter()
+                .enumerate()
+                .filter(|(_, &(c, escaped))| c == separators.kv && !escaped)
+                .map(|(i, _)| i)
+                .collect::<Vec<usize>>();
+            let Some(&last) = colons.last() else {
+                return Err(AppError::InvalidValueMapping(raw));
+            };
+            let part = |range: &[(char, bool)]| range.iter().map(|(c, _)| c).collect::<String>();
+
+            // text:value:description is tried first, so a numeric description like "A:1:5" is
+            // not mistaken for the text "A:1" with value 5
+            let mapping = colons.windows(2).find_map(|w| {
+                let value = part(&chars[w[0] + 1..w[1]]).parse::<i64>().ok()?;
+                let description = part(&chars[w[1] + 1..]);
+
+                Some(ValueMapping {
+                    text: part(&chars[..w[0]]),
+                    value,
+                    description: (!description.is_empty()).then_some(description),
+                })
+            });
+
+            if let Some(mapping) = mapping {
+                return Ok(mapping);
+            }
+
+            match part(&chars[last + 1..]).parse::<i64>() {
+                Ok(value) => Ok(ValueMapping {
+                    text: part(&chars[..last]),
+                    value,
+                    description: None,
+                }),
+                Err(_) => Err(AppError::InvalidValueMapping(raw)),
+            }
+        })
+        .collect()
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BacnetIpNonNumericSersorCsv {
-    id: String,
-    name: String,
+    pub id: String,
+    pub name: String,
     #[serde(alias = "objectInstance")]
-    object_instance: usize,
+    pub object_instance: usize,
     #[serde(alias = "objectType")]
-    object_type: String,
+    pub object_type: String,
     #[serde(alias = "sensorType")]
-    sensor_type: String,
+    pub sensor_type: String,
     #[serde(alias = "sensorTypeId")]
-    sensor_type_id: String,
+    pub sensor_type_id: String,
     #[serde(alias = "valueMapping")]
-    value_mapping: String,
+    pub value_mapping: String,
 }
 
+#[serde_as]
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BacnetIpNonNumericSensor {
     pub id: Option<String>,
     pub name: String,
     #[serde(alias = "objectInstance")]
-    object_instance: usize,
+    pub object_instance: usize,
     #[serde(alias = "objectType")]
-    object_type: String,
+    pub object_type: String,
     #[serde(alias = "sensorType")]
-    sensor_type: String,
+    pub sensor_type: String,
     #[serde(alias = "sensorTypeId")]
-    sensor_type_id: String,
+    pub sensor_type_id: String,
     #[serde(alias = "valueMapping")]
-    value_mapping: Vec<ValueMapping>,
+    pub value_mapping: Vec<ValueMapping>,
+    #[serde(default, skip_serializing)]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing, alias = "lastModified")]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl Timestamped for BacnetIpNonNumericSensor {
+    fn get_modified_at(&self) -> Option<DateTime<Utc>> {
+        self.modified_at.or(self.created_at)
+    }
 }
 
 impl fmt::Display for BacnetIpNonNumericSensor {
@@ -190,6 +787,14 @@ impl fmt::Display for BacnetIpNonNumericSensor {
     }
 }
 
+impl BacnetSensor for BacnetIpNonNumericSensor {
+    fn normalize_object_type(&mut self) -> Result<(), AppError> {
+        self.object_type = normalize_object_type(&self.object_type)?.to_string();
+
+        Ok(())
+    }
+}
+
 impl GenericSensor for BacnetIpNonNumericSensor {
     fn get_id_as_string(&self) -> String {
         match self.id.clone() {
@@ -198,17 +803,42 @@ impl GenericSensor for BacnetIpNonNumericSensor {
         }
     }
 
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_point_key(&self) -> String {
+        format!("{} {}", self.object_type, self.object_instance)
+    }
+
+    fn get_type_names(&self) -> Vec<String> {
+        vec![self.object_type.clone(), self.sensor_type.clone()]
+    }
+
     fn clean_empty_id(&mut self) {
         let id = self.get_id_as_string();
         if String::is_empty(&id) {
             self.id = None;
         }
     }
+
+    fn clear_id(&mut self) {
+        self.id = None;
+    }
+
+    fn is_same_sensor(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.object_instance == other.object_instance
+            && self.object_type == other.object_type
+    }
 }
 
 // The export wrapper is implemented because we have two potential serialization paths.
 // One for CSV export and another from the standard serde Serialize/De-Serialize funtionality
-pub struct BacnetIpNonNumericSensorExportWrapper(pub BacnetIpNonNumericSensor);
+pub struct BacnetIpNonNumericSensorExportWrapper(
+    pub BacnetIpNonNumericSensor,
+    pub MappingSeparators,
+);
 
 impl fmt::Display for BacnetIpNonNumericSensorExportWrapper {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -223,79 +853,66 @@ impl Serialize for BacnetIpNonNumericSensorExportWrapper {
     {
         let mut state = serializer.serialize_struct("BacnetIpNonNumericSensorExportWrapper", 7)?;
 
-        state.serialize_field("id", &self.0.id)?;
+        // A sensor without an id is written with an empty one, like numeric sensors in CSV
+        state.serialize_field("id", self.0.id.as_deref().unwrap_or_default())?;
         state.serialize_field("name", &self.0.name)?;
         state.serialize_field("objectInstance", &self.0.object_instance)?;
         state.serialize_field("objectType", &self.0.object_type)?;
         state.serialize_field("sensorType", &self.0.sensor_type)?;
         state.serialize_field("sensorTypeId", &self.0.sensor_type_id)?;
 
-        let value_mapping_str = self
-            .0
-            .value_mapping
-            .iter()
-            .map(|vm| format!("{}:{}", vm.text, vm.value))
-            .collect::<Vec<String>>()
-            .join(",");
-
-        state.serialize_field("valueMapping", &value_mapping_str)?;
+        state.serialize_field(
+            "valueMapping",
+            &format_value_mapping_with(&self.0.value_mapping, &self.1),
+        )?;
 
         state.end()
     }
 }
 
-impl From<BacnetIpNonNumericSersorCsv> for BacnetIpNonNumericSensor {
-    fn from(source: BacnetIpNonNumericSersorCsv) -> Self {
-        let mappings = source
-            .value_mapping
-            .split(',')
-            .filter_map(|pair| {
-                let mut parts = pair.splitn(2, ':');
-                Some(ValueMapping {
-                    text: parts.next()?.to_string(),
-                    value: parts
-                        .next()?
-                        .parse::<usize>()
-                        .expect("could not parse value to integer"),
-                })
-            })
-            .collect();
+impl FromSensorCsv<BacnetIpNonNumericSersorCsv> for BacnetIpNonNumericSensor {
+    fn from_csv(
+        source: BacnetIpNonNumericSersorCsv,
+        separators: &MappingSeparators,
+    ) -> Result<Self, AppError> {
+        let mappings = parse_value_mapping_with(&source.value_mapping, separators)?;
 
-        BacnetIpNonNumericSensor {
-            id: Some(source.id),
+        Ok(BacnetIpNonNumericSensor {
+            id: Some(source.id).filter(|id| !id.is_empty()),
             name: source.name,
             object_instance: source.object_instance,
             object_type: source.object_type,
             sensor_type: source.sensor_type,
             sensor_type_id: source.sensor_type_id,
             value_mapping: mappings,
-        }
+            ..Default::default()
+        })
     }
 }
 
 #[serde_as]
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SensorType {
-    #[serde(alias = "abbreviatedUnit")]
+    #[serde(rename = "abbreviatedUnit")]
     #[serde_as(deserialize_as = "DefaultOnError")]
-    _abbreviated_unit: String,
-    #[serde(alias = "isManuallyCreatable")]
-    _is_manually_creatable: bool,
-    #[serde(alias = "minimumValidValue")]
+    pub abbreviated_unit: String,
+    #[serde(rename = "isManuallyCreatable")]
+    pub _is_manually_creatable: bool,
+    #[serde(rename = "minimumValidValue")]
     #[serde_as(deserialize_as = "DefaultOnError")]
-    _minimum_valid_value: String,
-    #[serde(alias = "sensorDescription")]
-    sensor_description: String,
-    #[serde(alias = "sensorParentType")]
-    _sensor_parent_type: String,
-    #[serde(alias = "sensorTypeId")]
-    sensor_type_id: String,
-    #[serde(alias = "unitDescription")]
+    pub _minimum_valid_value: String,
+    #[serde(rename = "sensorDescription")]
+    pub sensor_description: String,
+    #[serde(rename = "sensorParentType")]
+    pub _sensor_parent_type: String,
+    #[serde(rename = "sensorTypeId")]
+    pub sensor_type_id: String,
+    #[serde(rename = "unitDescription")]
     #[serde_as(deserialize_as = "DefaultOnError")]
-    unit_description: String,
-    #[serde(alias = "unitId")]
+    pub unit_description: String,
+    #[serde(rename = "unitId")]
     #[serde_as(deserialize_as = "DefaultOnError")]
-    unit_id: String,
+    pub unit_id: String,
 }
 
 impl fmt::Display for SensorType {
@@ -308,27 +925,67 @@ impl fmt::Display for SensorType {
     }
 }
 
+// The columns of a sensor CSV a sensor type fills in
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensorTypeColumns {
+    pub sensor_type_id: String,
+    pub sensor_description: String,
+}
+
+impl From<SensorType> for SensorTypeColumns {
+    fn from(sensor_type: SensorType) -> Self {
+        SensorTypeColumns {
+            sensor_type_id: sensor_type.sensor_type_id,
+            sensor_description: sensor_type.sensor_description,
+        }
+    }
+}
+
+impl fmt::Display for SensorTypeColumns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "id: {}\ndescription: {}",
+            self.sensor_type_id, self.sensor_description
+        )
+    }
+}
+
 #[serde_as]
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModbusTcpNumericSensor {
     pub id: Option<String>,
     pub name: String,
-    multiplier: f64,
-    address: usize,
+    pub multiplier: f64,
+    #[serde(deserialize_with = "deserialize_modbus_address")]
+    pub address: usize,
     #[serde(alias = "registerType")]
-    register_type: String,
+    pub register_type: String,
     #[serde(alias = "dataSetting")]
-    data_setting: String,
+    pub data_setting: String,
     #[serde(alias = "sensorType")]
-    sensor_type: String,
+    pub sensor_type: String,
     #[serde(alias = "sensorTypeId")]
-    sensor_type_id: String,
+    pub sensor_type_id: String,
     #[serde_as(deserialize_as = "DefaultOnError")]
     pub unit: Option<String>,
     #[serde(alias = "unitId")]
     #[serde_as(deserialize_as = "DefaultOnError")]
     pub unit_id: Option<String>,
+    #[serde(default, skip_serializing)]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing, alias = "lastModified")]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl Timestamped for ModbusTcpNumericSensor {
+    fn get_modified_at(&self) -> Option<DateTime<Utc>> {
+        self.modified_at.or(self.created_at)
+    }
 }
 
 impl fmt::Display for ModbusTcpNumericSensor {
@@ -364,12 +1021,37 @@ impl GenericSensor for ModbusTcpNumericSensor {
         }
     }
 
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_point_key(&self) -> String {
+        format!("{} {}", self.register_type, self.address)
+    }
+
+    fn get_type_names(&self) -> Vec<String> {
+        vec![self.sensor_type.clone()]
+    }
+
     fn clean_empty_id(&mut self) {
         let id = self.get_id_as_string();
         if String::is_empty(&id) {
             self.id = None;
         }
     }
+
+    fn clear_id(&mut self) {
+        self.id = None;
+    }
+}
+
+impl ModbusSensor for ModbusTcpNumericSensor {
+    fn normalize_modbus_types(&mut self) -> Result<(), AppError> {
+        self.register_type = normalize_register_type(&self.register_type)?.to_string();
+        self.data_setting = normalize_data_type(&self.data_setting)?.to_string();
+
+        Ok(())
+    }
 }
 
 impl NumericSensor for ModbusTcpNumericSensor {
@@ -382,6 +1064,20 @@ impl NumericSensor for ModbusTcpNumericSensor {
             self.unit = None;
         }
     }
+
+    fn check_multiplier(&self) -> Result<(), AppError> {
+        check_multiplier(&self.name, self.multiplier)
+    }
+}
+
+// Numeric sensors have no value mappings, their CSV rows are read as the sensor itself
+impl FromSensorCsv<ModbusTcpNumericSensor> for ModbusTcpNumericSensor {
+    fn from_csv(
+        source: ModbusTcpNumericSensor,
+        _separators: &MappingSeparators,
+    ) -> Result<Self, AppError> {
+        Ok(source)
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -389,43 +1085,58 @@ impl NumericSensor for ModbusTcpNumericSensor {
 pub struct ModbusTcpNonNumericSensorCsv {
     pub id: Option<String>,
     pub name: String,
-    address: usize,
+    #[serde(deserialize_with = "deserialize_modbus_address")]
+    pub address: usize,
     #[serde(alias = "dataType")]
-    data_type: String,
+    pub data_type: String,
     #[serde(alias = "registerType")]
-    register_type: String,
+    pub register_type: String,
     #[serde(alias = "startBit")]
-    start_bit: usize,
+    pub start_bit: usize,
     #[serde(alias = "endBit")]
-    end_bit: usize,
+    pub end_bit: usize,
     #[serde(alias = "sensorType")]
-    sensor_type: String,
+    pub sensor_type: String,
     #[serde(alias = "sensorTypeId")]
-    sensor_type_id: String,
+    pub sensor_type_id: String,
     #[serde(alias = "valueMapping")]
-    value_mapping: String,
+    pub value_mapping: String,
 }
 
+#[serde_as]
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModbusTcpNonNumericSensor {
     pub id: Option<String>,
     pub name: String,
-    address: usize,
+    #[serde(deserialize_with = "deserialize_modbus_address")]
+    pub address: usize,
     #[serde(alias = "dataType")]
-    data_type: String,
+    pub data_type: String,
     #[serde(alias = "registerType")]
-    register_type: String,
+    pub register_type: String,
     #[serde(alias = "startBit")]
-    start_bit: usize,
+    pub start_bit: usize,
     #[serde(alias = "endBit")]
-    end_bit: usize,
+    pub end_bit: usize,
     #[serde(alias = "sensorType")]
-    sensor_type: String,
+    pub sensor_type: String,
     #[serde(alias = "sensorTypeId")]
-    sensor_type_id: String,
+    pub sensor_type_id: String,
     #[serde(alias = "valueMapping")]
-    value_mapping: Vec<ValueMapping>,
+    pub value_mapping: Vec<ValueMapping>,
+    #[serde(default, skip_serializing)]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing, alias = "lastModified")]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl Timestamped for ModbusTcpNonNumericSensor {
+    fn get_modified_at(&self) -> Option<DateTime<Utc>> {
+        self.modified_at.or(self.created_at)
+    }
 }
 
 impl fmt::Display for ModbusTcpNonNumericSensor {
@@ -456,17 +1167,54 @@ impl GenericSensor for ModbusTcpNonNumericSensor {
         }
     }
 
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_point_key(&self) -> String {
+        format!(
+            "{} {} bits {}-{}",
+            self.register_type, self.address, self.start_bit, self.end_bit
+        )
+    }
+
+    fn get_type_names(&self) -> Vec<String> {
+        vec![self.sensor_type.clone()]
+    }
+
     fn clean_empty_id(&mut self) {
         let id = self.get_id_as_string();
         if String::is_empty(&id) {
             self.id = None;
         }
     }
+
+    fn clear_id(&mut self) {
+        self.id = None;
+    }
+}
+
+impl ModbusSensor for ModbusTcpNonNumericSensor {
+    fn normalize_modbus_types(&mut self) -> Result<(), AppError> {
+        self.register_type = normalize_register_type(&self.register_type)?.to_string();
+        self.data_type = normalize_data_type(&self.data_type)?.to_string();
+
+        Ok(())
+    }
+}
+
+impl ModbusTcpNonNumericSensor {
+    pub fn validate_bit_range(&self) -> Result<(), AppError> {
+        validate_bit_range(self.start_bit, self.end_bit, &self.data_type)
+    }
 }
 
 // The export wrapper is implemented because we have two potential serialization paths.
 // One for CSV export and another from the standard serde Serialize/De-Serialize funtionality
-pub struct ModbusTcpNonNumericSensorExportWrapper(pub ModbusTcpNonNumericSensor);
+pub struct ModbusTcpNonNumericSensorExportWrapper(
+    pub ModbusTcpNonNumericSensor,
+    pub MappingSeparators,
+);
 
 impl fmt::Display for ModbusTcpNonNumericSensorExportWrapper {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -482,7 +1230,8 @@ impl Serialize for ModbusTcpNonNumericSensorExportWrapper {
         let mut state =
             serializer.serialize_struct("ModbusTcpNonNumericSensorExportWrapper", 10)?;
 
-        state.serialize_field("id", &self.0.id)?;
+        // A sensor without an id is written with an empty one, like numeric sensors in CSV
+        state.serialize_field("id", self.0.id.as_deref().unwrap_or_default())?;
         state.serialize_field("name", &self.0.name)?;
         state.serialize_field("address", &self.0.address)?;
         state.serialize_field("dataType", &self.0.data_type)?;
@@ -492,39 +1241,24 @@ impl Serialize for ModbusTcpNonNumericSensorExportWrapper {
         state.serialize_field("sensorType", &self.0.sensor_type)?;
         state.serialize_field("sensorTypeId", &self.0.sensor_type_id)?;
 
-        let value_mapping_str = self
-            .0
-            .value_mapping
-            .iter()
-            .map(|vm| format!("{}:{}", vm.text, vm.value))
-            .collect::<Vec<String>>()
-            .join(",");
-
-        state.serialize_field("valueMapping", &value_mapping_str)?;
+        state.serialize_field(
+            "valueMapping",
+            &format_value_mapping_with(&self.0.value_mapping, &self.1),
+        )?;
 
         state.end()
     }
 }
 
-impl From<ModbusTcpNonNumericSensorCsv> for ModbusTcpNonNumericSensor {
-    fn from(source: ModbusTcpNonNumericSensorCsv) -> Self {
-        let mappings = source
-            .value_mapping
-            .split(',')
-            .filter_map(|pair| {
-                let mut parts = pair.splitn(2, ':');
-                Some(ValueMapping {
-                    text: parts.next()?.to_string(),
-                    value: parts
-                        .next()?
-                        .parse::<usize>()
-                        .expect("could not parse value to integer"),
-                })
-            })
-            .collect();
+impl FromSensorCsv<ModbusTcpNonNumericSensorCsv> for ModbusTcpNonNumericSensor {
+    fn from_csv(
+        source: ModbusTcpNonNumericSensorCsv,
+        separators: &MappingSeparators,
+    ) -> Result<Self, AppError> {
+        let mappings = parse_value_mapping_with(&source.value_mapping, separators)?;
 
-        ModbusTcpNonNumericSensor {
-            id: source.id,
+        Ok(ModbusTcpNonNumericSensor {
+            id: source.id.filter(|id| !id.is_empty()),
             name: source.name,
             address: source.address,
             data_type: source.data_type,
@@ -534,7 +1268,8 @@ impl From<ModbusTcpNonNumericSensorCsv> for ModbusTcpNonNumericSensor {
             sensor_type: source.sensor_type,
             sensor_type_id: source.sensor_type_id,
             value_mapping: mappings,
-        }
+            ..Default::default()
+        })
     }
 }
 
@@ -544,24 +1279,30 @@ mod tests {
 
     #[test]
     fn test_bacnet_sensor_csv_serialization() {
-        let sensor = BacnetIpNonNumericSensorExportWrapper(BacnetIpNonNumericSensor {
-            id: Some("247a4ad9-9d18-4bf4-b20b-a1d7d61b3971".to_string()),
-            name: "Sensor 1".to_string(),
-            object_instance: 0,
-            object_type: "Temperature".to_string(),
-            sensor_type: "Analog".to_string(),
-            sensor_type_id: "1000".to_string(),
-            value_mapping: vec![
-                ValueMapping {
-                    text: "Low".to_string(),
-                    value: 0,
-                },
-                ValueMapping {
-                    text: "High".to_string(),
-                    value: 1,
-                },
-            ],
-        });
+        let sensor = BacnetIpNonNumericSensorExportWrapper(
+            BacnetIpNonNumericSensor {
+                id: Some("247a4ad9-9d18-4bf4-b20b-a1d7d61b3971".to_string()),
+                name: "Sensor 1".to_string(),
+                object_instance: 0,
+                object_type: "Temperature".to_string(),
+                sensor_type: "Analog".to_string(),
+                sensor_type_id: "1000".to_string(),
+                value_mapping: vec![
+                    ValueMapping {
+                        text: "Low".to_string(),
+                        value: 0,
+                        description: None,
+                    },
+                    ValueMapping {
+                        text: "High".to_string(),
+                        value: 1,
+                        description: None,
+                    },
+                ],
+                ..Default::default()
+            },
+            MappingSeparators::default(),
+        );
 
         let mut wtr = csv::Writer::from_writer(vec![]);
         wtr.serialize(&sensor).expect("Failed to serialize sensor");
@@ -576,27 +1317,33 @@ mod tests {
 
     #[test]
     fn test_modbus_sensor_csv_serialization() {
-        let sensor = ModbusTcpNonNumericSensorExportWrapper(ModbusTcpNonNumericSensor {
-            id: Some("ffd733e3-2ee2-4e81-a688-2483cb011698".to_string()),
-            name: "Clogged filter 1".to_string(),
-            address: 1,
-            data_type: "uInteger16".to_string(),
-            register_type: "holdingRegister".to_string(),
-            start_bit: 1,
-            end_bit: 16,
-            sensor_type: "cloggedFilter".to_string(),
-            sensor_type_id: "f4531ff2-ebf8-49d2-bd4f-4d64c39e4283".to_string(),
-            value_mapping: vec![
-                ValueMapping {
-                    text: "Inactive".to_string(),
-                    value: 0,
-                },
-                ValueMapping {
-                    text: "Active".to_string(),
-                    value: 1,
-                },
-            ],
-        });
+        let sensor = ModbusTcpNonNumericSensorExportWrapper(
+            ModbusTcpNonNumericSensor {
+                id: Some("ffd733e3-2ee2-4e81-a688-2483cb011698".to_string()),
+                name: "Clogged filter 1".to_string(),
+                address: 1,
+                data_type: "uInteger16".to_string(),
+                register_type: "holdingRegister".to_string(),
+                start_bit: 1,
+                end_bit: 16,
+                sensor_type: "cloggedFilter".to_string(),
+                sensor_type_id: "f4531ff2-ebf8-49d2-bd4f-4d64c39e4283".to_string(),
+                value_mapping: vec![
+                    ValueMapping {
+                        text: "Inactive".to_string(),
+                        value: 0,
+                        description: None,
+                    },
+                    ValueMapping {
+                        text: "Active".to_string(),
+                        value: 1,
+                        description: None,
+                    },
+                ],
+                ..Default::default()
+            },
+            MappingSeparators::default(),
+        );
 
         let mut wtr = csv::Writer::from_writer(vec![]);
         wtr.serialize(&sensor).expect("Failed to serialize sensor");
@@ -609,6 +1356,237 @@ mod tests {
         assert_eq!(data, expected_data);
     }
 
+    // Generic over the type written and the type read back, like a list export and an import
+    fn round_trip_csv<W: Serialize, R: serde::de::DeserializeOwned>(sensor: W) -> (String, R) {
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        wtr.serialize(sensor).unwrap();
+        let data = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        let read = csv::Reader::from_reader(data.as_bytes())
+            .deserialize::<R>()
+            .next()
+            .unwrap()
+            .unwrap();
+
+        (data.lines().nth(1).unwrap().to_string(), read)
+    }
+
+    #[test]
+    fn test_sensor_type_columns() {
+        let sensor_type = SensorType {
+            sensor_type_id: "f4531ff2-ebf8-49d2-bd4f-4d64c39e4283".to_string(),
+            sensor_description: "Clogged Filter".to_string(),
+            unit_id: "d53e036e-a428-4c1a-b779-8322b96dfe16".to_string(),
+            ..Default::default()
+        };
+
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        wtr.serialize(SensorTypeColumns::from(sensor_type)).unwrap();
+        let data = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+
+        assert_eq!(
+            data,
+            "sensorTypeId,sensorDescription\nf4531ff2-ebf8-49d2-bd4f-4d64c39e4283,Clogged Filter\n"
+        );
+    }
+
+    #[test]
+    fn test_blank_id_round_trip() {
+        let (row, sensor): (_, BacnetIpNumericSensor) =
+            round_trip_csv(BacnetIpNumericSensor::default());
+        assert!(row.starts_with(",,"));
+        assert_eq!(sensor.id, None);
+
+        let (row, sensor): (_, ModbusTcpNumericSensor) =
+            round_trip_csv(ModbusTcpNumericSensor::default());
+        assert!(row.starts_with(",,"));
+        assert_eq!(sensor.id, None);
+
+        let (row, sensor_csv): (_, BacnetIpNonNumericSersorCsv) =
+            round_trip_csv(BacnetIpNonNumericSensorExportWrapper(
+                BacnetIpNonNumericSensor::default(),
+                MappingSeparators::default(),
+            ));
+        assert!(row.starts_with(",,"));
+        assert_eq!(
+            BacnetIpNonNumericSensor::from_csv(sensor_csv, &MappingSeparators::default())
+                .unwrap()
+                .id,
+            None
+        );
+
+        let (row, sensor_csv): (_, ModbusTcpNonNumericSensorCsv) =
+            round_trip_csv(ModbusTcpNonNumericSensorExportWrapper(
+                ModbusTcpNonNumericSensor {
+                    data_type: "bool".to_string(),
+                    ..Default::default()
+                },
+                MappingSeparators::default(),
+            ));
+        assert!(row.starts_with(",,"));
+        assert_eq!(
+            ModbusTcpNonNumericSensor::from_csv(sensor_csv, &MappingSeparators::default())
+                .unwrap()
+                .id,
+            None
+        );
+
+        // JSON exports of non-numeric sensors use the same empty id
+        let json = serde_json::to_value(BacnetIpNonNumericSensorExportWrapper(
+            BacnetIpNonNumericSensor::default(),
+            MappingSeparators::default(),
+        ))
+        .unwrap();
+        assert_eq!(json["id"], "");
+    }
+
+    #[test]
+    fn test_non_numeric_sensor_value_mapping_round_trip() {
+        let value_mapping = vec![
+            ValueMapping {
+                text: "Fault".to_string(),
+                value: -1,
+                description: None,
+            },
+            ValueMapping {
+                text: "Ok".to_string(),
+                value: 0,
+                description: None,
+            },
+            ValueMapping {
+                text: "Large".to_string(),
+                value: 5_000_000_000,
+                description: None,
+            },
+        ];
+
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        wtr.serialize(BacnetIpNonNumericSensorExportWrapper(
+            BacnetIpNonNumericSensor {
+                name: "Sensor 1".to_string(),
+                value_mapping: value_mapping.clone(),
+                ..Default::default()
+            },
+            MappingSeparators::default(),
+        ))
+        .expect("Failed to serialize sensor");
+        let data = wtr.into_inner().expect("Failed to get inner writer");
+
+        let sensor_csv = csv::Reader::from_reader(data.as_slice())
+            .deserialize::<BacnetIpNonNumericSersorCsv>()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(sensor_csv.value_mapping, "Fault:-1,Ok:0,Large:5000000000");
+        assert_eq!(
+            BacnetIpNonNumericSensor::from_csv(sensor_csv, &MappingSeparators::default())
+                .unwrap()
+                .value_mapping,
+            value_mapping
+        );
+
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        wtr.serialize(ModbusTcpNonNumericSensorExportWrapper(
+            ModbusTcpNonNumericSensor {
+                name: "Clogged filter 1".to_string(),
+                value_mapping: value_mapping.clone(),
+                ..Default::default()
+            },
+            MappingSeparators::default(),
+        ))
+        .expect("Failed to serialize sensor");
+        let data = wtr.into_inner().expect("Failed to get inner writer");
+
+        let sensor_csv = csv::Reader::from_reader(data.as_slice())
+            .deserialize::<ModbusTcpNonNumericSensorCsv>()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            ModbusTcpNonNumericSensor::from_csv(sensor_csv, &MappingSeparators::default())
+                .unwrap()
+                .value_mapping,
+            value_mapping
+        );
+    }
+
+    #[test]
+    fn test_modbus_hex_address() {
+        let sensors = "name,address,dataType,registerType,startBit,endBit,sensorType,sensorTypeId,valueMapping
+hex,0x10,uInteger16,holdingRegister,1,16,filter,,Off:0
+decimal,16,uInteger16,holdingRegister,1,16,filter,,Off:0
+invalid,0xZZ,uInteger16,holdingRegister,1,16,filter,,Off:0
+";
+        let mut reader = csv::Reader::from_reader(sensors.as_bytes());
+        let sensors = reader
+            .deserialize::<ModbusTcpNonNumericSensorCsv>()
+            .collect::<Vec<_>>();
+
+        assert_eq!(sensors[0].as_ref().unwrap().address, 16);
+        assert_eq!(sensors[1].as_ref().unwrap().address, 16);
+        assert!(sensors[2]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("invalid value: string \"0xZZ\", expected a decimal Modbus address"));
+
+        let sensor: ModbusTcpNumericSensor =
+            serde_json::from_str(r#"{"name":"Power","multiplier":1.0,"address":40001,"registerType":"holdingRegister","dataSetting":"uInteger16","sensorType":"power","sensorTypeId":"","unit":null,"unitId":null}"#)
+                .unwrap();
+        assert_eq!(sensor.address, 40001);
+    }
+
+    #[test]
+    fn test_sensor_type_csv_serialization() {
+        let sensor_type: SensorType = serde_json::from_str(
+            r#"{
+                "abbreviatedUnit": "°C",
+                "isManuallyCreatable": true,
+                "minimumValidValue": null,
+                "sensorDescription": "Temperature",
+                "sensorParentType": "temperature",
+                "sensorTypeId": "0822ef0a-d0de-4789-9f44-51833c48e7a0",
+                "unitDescription": "Celsius",
+                "unitId": "16b7b95b-c188-456b-ba53-08c028988cd3"
+            }"#,
+        )
+        .unwrap();
+
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        wtr.serialize(&sensor_type)
+            .expect("Failed to serialize sensor type");
+
+        let data = String::from_utf8(wtr.into_inner().expect("Failed to get inner writer"))
+            .expect("Failed to convert to string");
+
+        assert_eq!(
+            data.lines().next().unwrap(),
+            "abbreviatedUnit,isManuallyCreatable,minimumValidValue,sensorDescription,sensorParentType,sensorTypeId,unitDescription,unitId"
+        );
+        assert_eq!(
+            data.lines().nth(1).unwrap(),
+            "°C,true,,Temperature,temperature,0822ef0a-d0de-4789-9f44-51833c48e7a0,Celsius,16b7b95b-c188-456b-ba53-08c028988cd3"
+        );
+    }
+
+    #[test]
+    fn test_definition_display_sensor_counts() {
+        let mut definition = Definition {
+            id: Some("5e2b7d1a-51d1-4c5e-9a9f-1d0f4b3f7c11".to_string()),
+            name: "Chiller".to_string(),
+            asset_type: "Chiller".to_string(),
+            ..Default::default()
+        };
+        assert!(!definition.to_string().contains("sensors"));
+
+        definition.sensor_counts = Some(SensorCounts {
+            numeric: Some(12),
+            non_numeric: None,
+        });
+        assert!(definition
+            .to_string()
+            .ends_with("numeric sensors: 12\nnon-numeric sensors: unavailable"));
+    }
+
     #[test]
     fn test_get_id_as_string() {
         let mut sensor = BacnetIpNumericSensor {
@@ -672,6 +1650,43 @@ mod tests {
         assert_eq!(sensor.get_id_as_string(), "".to_string());
     }
 
+    #[test]
+    fn test_is_same_sensor() {
+        let sensor = BacnetIpNumericSensor {
+            name: "Supply Temperature".to_string(),
+            object_instance: 1,
+            object_type: "analogInput".to_string(),
+            ..Default::default()
+        };
+
+        assert!(sensor.is_same_sensor(&BacnetIpNumericSensor {
+            id: Some("13d2cbd0-77c0-49a4-b9c8-38d91ce957d8".to_string()),
+            name: "Supply Temperature".to_string(),
+            object_instance: 1,
+            object_type: "analogInput".to_string(),
+            multiplier: 0.1,
+            ..Default::default()
+        }));
+        assert!(!sensor.is_same_sensor(&BacnetIpNumericSensor {
+            name: "Supply Temperature".to_string(),
+            object_instance: 2,
+            object_type: "analogInput".to_string(),
+            ..Default::default()
+        }));
+
+        let sensor = ModbusTcpNumericSensor {
+            name: "Supply Temperature".to_string(),
+            address: 1,
+            ..Default::default()
+        };
+
+        assert!(sensor.is_same_sensor(&ModbusTcpNumericSensor {
+            name: "Supply Temperature".to_string(),
+            address: 2,
+            ..Default::default()
+        }));
+    }
+
     #[test]
     fn test_clean_empty_id() {
         let mut sensor = BacnetIpNumericSensor {
@@ -782,4 +1797,310 @@ mod tests {
         sensor.clean_empty_id();
         assert_eq!(sensor.id, None);
     }
+
+    #[test]
+    fn test_normalize_object_type() {
+        for object_type in BACNET_OBJECT_TYPES {
+            assert_eq!(normalize_object_type(object_type).unwrap(), object_type);
+        }
+
+        let mut sensor = BacnetIpNumericSensor {
+            object_type: "AnalogInput".to_string(),
+            ..Default::default()
+        };
+        sensor.normalize_object_type().unwrap();
+        assert_eq!(sensor.object_type, "analogInput");
+
+        let mut sensor = BacnetIpNonNumericSensor {
+            object_type: "binaryVal".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            sensor.normalize_object_type(),
+            Err(AppError::InvalidObjectType(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_normalize_modbus_types() {
+        for register_type in MODBUS_REGISTER_TYPES {
+            assert_eq!(
+                normalize_register_type(register_type).unwrap(),
+                register_type
+            );
+        }
+
+        for data_type in MODBUS_DATA_TYPES {
+            assert_eq!(normalize_data_type(data_type).unwrap(), data_type);
+        }
+
+        let mut sensor = ModbusTcpNumericSensor {
+            register_type: "HoldingRegister".to_string(),
+            data_setting: "uinteger32bigendian".to_string(),
+            ..Default::default()
+        };
+        sensor.normalize_modbus_types().unwrap();
+        assert_eq!(sensor.register_type, "holdingRegister");
+        assert_eq!(sensor.data_setting, "uInteger32BigEndian");
+
+        let mut sensor = ModbusTcpNonNumericSensor {
+            register_type: "holdingRegisters".to_string(),
+            data_type: "uInteger16".to_string(),
+            ..Default::default()
+        };
+        match sensor.normalize_modbus_types() {
+            Err(e) => assert_eq!(
+                e.to_string(),
+                "Invalid register type \"holdingRegisters\", valid values are: holdingRegister, inputRegister, coil, discreteInput"
+            ),
+            _ => panic!("Expected Err, but got Ok"),
+        }
+
+        let mut sensor = ModbusTcpNonNumericSensor {
+            register_type: "coil".to_string(),
+            data_type: "uInteger8".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            sensor.normalize_modbus_types(),
+            Err(AppError::InvalidDataType(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_non_numeric_sensor_from_csv_invalid_value_mapping() {
+        let sensor_csv = ModbusTcpNonNumericSensorCsv {
+            name: "Clogged filter".to_string(),
+            value_mapping: "Low:abc".to_string(),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            ModbusTcpNonNumericSensor::from_csv(sensor_csv, &MappingSeparators::default()),
+            Err(AppError::InvalidValueMapping(pair)) if pair == "Low:abc"
+        ));
+    }
+
+    #[test]
+    fn test_validate_bit_range() {
+        assert!(validate_bit_range(0, 15, "uInteger16").is_ok());
+        assert!(validate_bit_range(1, 16, "uInteger16").is_ok());
+        assert!(validate_bit_range(3, 3, "integer32BigEndian").is_ok());
+        assert!(validate_bit_range(0, 31, "float32LittleEndian").is_ok());
+        assert!(validate_bit_range(0, 1, "boolean").is_ok());
+
+        assert!(matches!(
+            validate_bit_range(20, 3, "uInteger16"),
+            Err(AppError::InvalidBitRange(20, 3, _))
+        ));
+        assert!(matches!(
+            validate_bit_range(0, 17, "integer16"),
+            Err(AppError::InvalidBitRange(0, 17, _))
+        ));
+        assert!(validate_bit_range(0, 16, "uInteger16").is_err());
+        assert!(validate_bit_range(0, 32, "integer32LittleEndian").is_err());
+        assert!(validate_bit_range(0, 17, "boolean").is_err());
+    }
+
+    #[test]
+    fn test_parse_value_mapping() {
+        assert_eq!(
+            parse_value_mapping_with("Inactive:0,Active:1", &MappingSeparators::default()).unwrap(),
+            vec![
+                ValueMapping {
+                    text: "Inactive".to_string(),
+                    value: 0,
+                    description: None,
+                },
+                ValueMapping {
+                    text: "Active".to_string(),
+                    value: 1,
+                    description: None,
+                },
+            ]
+        );
+
+        match parse_value_mapping_with("Low:0,High:abc", &MappingSeparators::default()) {
+            Err(e) => assert_eq!(
+                e.to_string(),
+                AppError::InvalidValueMapping("High:abc".to_string()).to_string()
+            ),
+            _ => panic!("Expected Err, but got Ok"),
+        }
+    }
+
+    #[test]
+    fn test_parse_value_mapping_invalid_pairs() {
+        match parse_value_mapping_with("Low,High:1", &MappingSeparators::default()) {
+            Err(e) => assert_eq!(
+                e.to_string(),
+                AppError::InvalidValueMapping("Low".to_string()).to_string()
+            ),
+            _ => panic!("Expected Err, but got Ok"),
+        }
+
+        match parse_value_mapping_with("Low:0,High:1\\", &MappingSeparators::default()) {
+            Err(e) => assert_eq!(
+                e.to_string(),
+                AppError::InvalidValueMapping("High:1\\".to_string()).to_string()
+            ),
+            _ => panic!("Expected Err, but got Ok"),
+        }
+
+        assert_eq!(
+            parse_value_mapping_with("", &MappingSeparators::default()).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_value_mapping_with_delimiters_round_trip() {
+        let value_mapping = vec![
+            ValueMapping {
+                text: "High: Critical".to_string(),
+                value: 2,
+                description: None,
+            },
+            ValueMapping {
+                text: "On, manual".to_string(),
+                value: 1,
+                description: None,
+            },
+            ValueMapping {
+                text: "C:\\temp".to_string(),
+                value: -1,
+                description: None,
+            },
+        ];
+
+        let formatted = format_value_mapping_with(&value_mapping, &MappingSeparators::default());
+
+        assert_eq!(
+            formatted,
+            "High\\: Critical:2,On\\, manual:1,C\\:\\\\temp:-1"
+        );
+        assert_eq!(
+            parse_value_mapping_with(&formatted, &MappingSeparators::default()).unwrap(),
+            value_mapping
+        );
+
+        // Unescaped colons in the text are accepted as long as no number follows them
+        assert_eq!(
+            parse_value_mapping_with("High: Critical:2", &MappingSeparators::default()).unwrap(),
+            vec![ValueMapping {
+                text: "High: Critical".to_string(),
+                value: 2,
+                description: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_value_mapping_description_round_trip() {
+        let value_mapping = vec![
+            ValueMapping {
+                text: "Normal".to_string(),
+                value: 0,
+                description: None,
+            },
+            ValueMapping {
+                text: "Fault".to_string(),
+                value: 2,
+                description: Some("Check belt: loose, worn".to_string()),
+            },
+        ];
+
+        let formatted = format_value_mapping_with(&value_mapping, &MappingSeparators::default());
+
+        assert_eq!(formatted, "Normal:0,Fault:2:Check belt\\: loose\\, worn");
+        assert_eq!(
+            parse_value_mapping_with(&formatted, &MappingSeparators::default()).unwrap(),
+            value_mapping
+        );
+
+        // Unescaped colons are accepted in the text and the description
+        assert_eq!(
+            parse_value_mapping_with(
+                "High: Critical:2:Call: facilities",
+                &MappingSeparators::default()
+            )
+            .unwrap(),
+            vec![ValueMapping {
+                text: "High: Critical".to_string(),
+                value: 2,
+                description: Some("Call: facilities".to_string()),
+            }]
+        );
+
+        // A numeric description is kept as the description
+        let value_mapping = vec![ValueMapping {
+            text: "A".to_string(),
+            value: 1,
+            description: Some("5".to_string()),
+        }];
+        let formatted = format_value_mapping_with(&value_mapping, &MappingSeparators::default());
+
+        assert_eq!(formatted, "A:1:5");
+        assert_eq!(
+            parse_value_mapping_with(&formatted, &MappingSeparators::default()).unwrap(),
+            value_mapping
+        );
+
+        let (_, sensor_csv): (_, BacnetIpNonNumericSersorCsv) =
+            round_trip_csv(BacnetIpNonNumericSensorExportWrapper(
+                BacnetIpNonNumericSensor {
+                    value_mapping: value_mapping.clone(),
+                    ..Default::default()
+                },
+                MappingSeparators::default(),
+            ));
+        assert_eq!(
+            BacnetIpNonNumericSensor::from_csv(sensor_csv, &MappingSeparators::default())
+                .unwrap()
+                .value_mapping,
+            value_mapping
+        );
+    }
+
+    #[test]
+    fn test_value_mapping_custom_separators() {
+        let separators = MappingSeparators { pair: '|', kv: '=' };
+        let value_mapping = vec![
+            ValueMapping {
+                text: "High: Critical, now".to_string(),
+                value: 2,
+                description: Some("a=b".to_string()),
+            },
+            ValueMapping {
+                text: "Off|idle".to_string(),
+                value: 0,
+                description: None,
+            },
+        ];
+
+        let formatted = format_value_mapping_with(&value_mapping, &separators);
+
+        assert_eq!(formatted, "High: Critical, now=2=a\\=b|Off\\|idle=0");
+        assert_eq!(
+            parse_value_mapping_with(&formatted, &separators).unwrap(),
+            value_mapping
+        );
+
+        // Export and import of a sensor use the separators they are given
+        let (_, sensor_csv): (_, ModbusTcpNonNumericSensorCsv) =
+            round_trip_csv(ModbusTcpNonNumericSensorExportWrapper(
+                ModbusTcpNonNumericSensor {
+                    value_mapping: value_mapping.clone(),
+                    ..Default::default()
+                },
+                separators,
+            ));
+        assert_eq!(sensor_csv.value_mapping, formatted);
+        assert_eq!(
+            ModbusTcpNonNumericSensor::from_csv(sensor_csv, &separators)
+                .unwrap()
+                .value_mapping,
+            value_mapping
+        );
+    }
 }
@@ -1,5 +1,31 @@
+use csv::DeserializeErrorKind;
+use oauth2::basic::BasicRequestTokenError;
+use reqwest::StatusCode;
 use thiserror::Error;
 
+// Exit codes, documented in the README so scripts can branch on them
+pub const EXIT_FAILURE: u8 = 1;
+pub const EXIT_USAGE: u8 = 2;
+pub const EXIT_AUTH: u8 = 3;
+pub const EXIT_NETWORK: u8 = 4;
+pub const EXIT_IMPORT_FAILED: u8 = 5;
+// 128 + SIGINT, like a shell reports a process ended by Ctrl-C
+pub const EXIT_INTERRUPTED: u8 = 130;
+
+// csv puts the position in its own messages, which CsvParse already shows as the line
+fn describe_csv_error(e: &csv::Error) -> String {
+    match e.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => match (err.field(), err.kind()) {
+            (Some(field), DeserializeErrorKind::Message(m)) => {
+                format!("field {}: {}", field + 1, m)
+            }
+            (Some(field), kind) => format!("field {}: {}", field + 1, kind),
+            (None, kind) => kind.to_string(),
+        },
+        _ => e.to_string(),
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Input file does not exist")]
@@ -10,4 +36,256 @@ pub enum AppError {
 
     #[error("Must provide an output filename")]
     NoOutputFilename,
+
+    #[error("Invalid register type \"{0}\", valid values are: {1}")]
+    InvalidRegisterType(String, String),
+
+    #[error("Invalid object type \"{0}\", valid values are: {1}")]
+    InvalidObjectType(String, String),
+
+    #[error("Invalid data type \"{0}\", valid values are: {1}")]
+    InvalidDataType(String, String),
+
+    #[error("Invalid date \"{0}\", expected e.g. 2024-01-15, 2024-01-15T14:30:00 or 2024-01-15T14:30:00Z")]
+    InvalidDateTime(String),
+
+    #[error("Invalid delimiter \"{0}\", expected a single character or \\t")]
+    InvalidDelimiter(String),
+
+    #[error("Invalid value mapping separator \"{0}\", expected a single character other than a backslash")]
+    InvalidMappingSeparator(String),
+
+    #[error("The value mapping pair and key-value separators must differ, both are \"{0}\"")]
+    SameMappingSeparators(char),
+
+    #[error("Invalid definition id \"{0}\", expected a UUID")]
+    InvalidDefinitionId(String),
+
+    #[error("Invalid asset type \"{0}\", valid values are: {1}")]
+    InvalidAssetType(String, String),
+
+    #[error("Invalid asset type \"{0}\", did you mean {1}?")]
+    UnknownAssetType(String, String),
+
+    #[error("No sensor type matches \"{0}\"")]
+    SensorTypeNotFound(String),
+
+    #[error("Sensor type \"{0}\" is ambiguous, matching ids: {1}")]
+    AmbiguousSensorType(String, String),
+
+    #[error("No unit \"{0}\" found for sensor type \"{1}\"")]
+    UnitNotFound(String, String),
+
+    #[error("Could not fetch sensor types: {0}")]
+    SensorTypesUnavailable(String),
+
+    #[error("No cached {1} sensor types of asset type \"{0}\", run list-sensor-types once with access to the server")]
+    SensorTypesNotCached(String, String),
+
+    #[error("Sensor \"{0}\" has multiplier {1}, which makes every reading useless")]
+    InvalidMultiplier(String, f64),
+
+    #[error("Invalid bit range {0}-{1} for data type \"{2}\"")]
+    InvalidBitRange(usize, usize, String),
+
+    #[error("Definition {0} not found")]
+    DefinitionNotFound(String),
+
+    #[error("No definition named \"{0}\"")]
+    DefinitionNameNotFound(String),
+
+    #[error("Definition name \"{0}\" is ambiguous, matching ids: {1}")]
+    AmbiguousDefinitionName(String, String),
+
+    #[error("Server kept rate limiting requests after waiting {0} seconds")]
+    RateLimited(u64),
+
+    #[error("Invalid value mapping \"{0}\", expected text:value or text:value:description")]
+    InvalidValueMapping(String),
+
+    #[error("Import file has duplicate sensors: {0}")]
+    DuplicateSensors(String),
+
+    #[error("{0} responded with {1}: {2}")]
+    UploadRejected(String, StatusCode, String),
+
+    #[error("Target definition already has sensors: {0}")]
+    SensorsExist(String),
+
+    #[error("{0} sensors could not be imported")]
+    ImportFailed(usize),
+
+    #[error("Verification failed, {0} sensors missing and {1} sensors different")]
+    VerificationFailed(usize, usize),
+
+    #[error("Interrupted, {0} sensors were not imported")]
+    Interrupted(usize),
+
+    #[error("{0} sensors could not be deleted")]
+    DeleteFailed(usize),
+
+    #[error("Confirmation needed but not running in a terminal, pass --yes to confirm")]
+    ConfirmationRequired,
+
+    #[error("Can't sort by \"{0}\", valid fields are: {1}")]
+    InvalidSortField(String, String),
+
+    #[error("Unknown column \"{0}\", valid columns are: {1}")]
+    InvalidColumn(String, String),
+
+    #[error("Checkpoint {0} was written for another import file or definition, or the file changed since")]
+    CheckpointMismatch(String),
+
+    #[error("Unsupported bundle version {0}, this version of dit reads version {1}")]
+    UnsupportedBundleVersion(u32, u32),
+
+    #[error("Workbook {0} has no sheets")]
+    EmptyWorkbook(String),
+
+    #[error("Validation failed")]
+    ValidationFailed,
+
+    #[error("{0} configuration checks failed")]
+    InvalidConfig(usize),
+
+    #[error("Invalid CSV on line {line}: {}", describe_csv_error(source))]
+    CsvParse { line: u64, source: csv::Error },
+
+    #[error("Invalid JSON in {context}: {source}")]
+    JsonParse {
+        context: String,
+        source: serde_json::Error,
+    },
+
+    #[error("Invalid keyring reference \"{0}\", expected keyring:<service>/<user>")]
+    InvalidKeyringReference(String),
+
+    #[error("Could not read {0} from the keyring: {1}")]
+    KeyringSecretUnavailable(String, String),
+
+    #[error("Could not load CA certificate {0}: {1}")]
+    InvalidCaCert(String, String),
+
+    #[error("Invalid request id header name \"{0}\"")]
+    InvalidRequestIdHeader(String),
+
+    #[error("Request to {0} timed out, use --timeout-secs to wait longer")]
+    Timeout(String),
+
+    #[error("Could not connect to {0}: {1}. Check the URL and the network or proxy settings")]
+    Network(String, String),
+
+    #[error("Secure connection to {0} failed: {1}. Use ca_cert or --ca-cert if the instance uses a certificate from an internal CA")]
+    Tls(String, String),
+
+    #[error("Authentication failed: {0}")]
+    AuthenticationFailed(String),
+
+    #[error("Authentication failed for {0}, check client_id, client_secret, scope and that token_url is correct")]
+    Unauthorized(String),
+
+    #[error("Authenticated but not authorized for {0}")]
+    Forbidden(String),
+
+    #[error("{0} returned {1}")]
+    UnexpectedStatus(String, StatusCode),
+}
+
+impl AppError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            AppError::InputFileDoesNotExist
+            | AppError::FileExists
+            | AppError::NoOutputFilename
+            | AppError::InvalidRegisterType(..)
+            | AppError::InvalidObjectType(..)
+            | AppError::InvalidDataType(..)
+            | AppError::InvalidDelimiter(_)
+            | AppError::InvalidMappingSeparator(_)
+            | AppError::SameMappingSeparators(_)
+            | AppError::InvalidDateTime(_)
+            | AppError::InvalidDefinitionId(_)
+            | AppError::InvalidAssetType(..)
+            | AppError::UnknownAssetType(..)
+            | AppError::SensorTypeNotFound(_)
+            | AppError::AmbiguousSensorType(..)
+            | AppError::UnitNotFound(..)
+            | AppError::SensorTypesNotCached(..)
+            | AppError::InvalidBitRange(..)
+            | AppError::InvalidMultiplier(..)
+            | AppError::DefinitionNotFound(_)
+            | AppError::DefinitionNameNotFound(_)
+            | AppError::AmbiguousDefinitionName(..)
+            | AppError::InvalidValueMapping(_)
+            | AppError::DuplicateSensors(_)
+            | AppError::SensorsExist(_)
+            | AppError::InvalidSortField(..)
+            | AppError::InvalidColumn(..)
+            | AppError::UnsupportedBundleVersion(..)
+            | AppError::CheckpointMismatch(_)
+            | AppError::EmptyWorkbook(_)
+            | AppError::ValidationFailed
+            | AppError::InvalidConfig(_)
+            | AppError::CsvParse { .. }
+            | AppError::InvalidKeyringReference(_)
+            | AppError::InvalidCaCert(..)
+            | AppError::InvalidRequestIdHeader(_)
+            | AppError::ConfirmationRequired => EXIT_USAGE,
+            AppError::KeyringSecretUnavailable(..)
+            | AppError::AuthenticationFailed(_)
+            | AppError::Unauthorized(_)
+            | AppError::Forbidden(_) => EXIT_AUTH,
+            AppError::RateLimited(_)
+            | AppError::Timeout(_)
+            | AppError::Network(..)
+            | AppError::Tls(..) => EXIT_NETWORK,
+            AppError::UploadRejected(..)
+            | AppError::ImportFailed(_)
+            | AppError::VerificationFailed(..)
+            | AppError::DeleteFailed(_) => EXIT_IMPORT_FAILED,
+            AppError::Interrupted(_) => EXIT_INTERRUPTED,
+            AppError::SensorTypesUnavailable(_)
+            | AppError::JsonParse { .. }
+            | AppError::UnexpectedStatus(..) => EXIT_FAILURE,
+        }
+    }
+}
+
+// Errors from confy and oauth2 are not wrapped in an AppError, but still have a clear category
+pub fn get_exit_code(e: &anyhow::Error) -> u8 {
+    if let Some(app_error) = e.chain().find_map(|c| c.downcast_ref::<AppError>()) {
+        return app_error.exit_code();
+    }
+
+    if e.chain().any(|c| c.is::<confy::ConfyError>()) {
+        EXIT_USAGE
+    } else if e
+        .chain()
+        .any(|c| c.is::<BasicRequestTokenError<reqwest::Error>>())
+    {
+        EXIT_AUTH
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_get_exit_code() {
+        let e: anyhow::Error = AppError::ImportFailed(2).into();
+        assert_eq!(get_exit_code(&e), EXIT_IMPORT_FAILED);
+
+        let e = Err::<(), _>(AppError::Unauthorized(
+            "https://example.hyperviewhq.com".into(),
+        ))
+        .context("Could not list definitions")
+        .unwrap_err();
+        assert_eq!(get_exit_code(&e), EXIT_AUTH);
+
+        assert_eq!(get_exit_code(&anyhow::anyhow!("unexpected")), EXIT_FAILURE);
+    }
 }
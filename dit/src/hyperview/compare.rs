@@ -0,0 +1,709 @@
+use anyhow::{anyhow, Result};
+use csv::StringRecord;
+use reqwest::blocking::Client;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+use super::{
+    api::{get_definition, list_sensors, RetryPolicy},
+    api_data::{
+        BacnetIpNonNumericSensor, BacnetIpNonNumericSersorCsv, BacnetIpNumericSensor, Definition,
+        DefinitionDataType, DefinitionType, FromSensorCsv, GenericSensor, MappingSeparators,
+        ModbusTcpNonNumericSensor, ModbusTcpNonNumericSensorCsv, ModbusTcpNumericSensor,
+        NumericSensor,
+    },
+    app_errors::AppError,
+    cli::{
+        get_definition_data_type, get_definition_type, get_template_headers, open_csv_reader,
+        AppConfig, CompareDefinitionsArgs, DiffArgs,
+    },
+};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FieldDifference {
+    pub field: String,
+    pub first: Value,
+    pub second: Value,
+}
+
+impl fmt::Display for FieldDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.first, self.second)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SensorComparison {
+    pub only_in_first: Vec<String>,
+    pub only_in_second: Vec<String>,
+    pub changed: Vec<(String, Vec<FieldDifference>)>,
+    pub unchanged: usize,
+}
+
+impl fmt::Display for SensorComparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "unchanged: {}", self.unchanged)?;
+
+        for name in &self.only_in_first {
+            writeln!(f, "only in source: {}", name)?;
+        }
+
+        for name in &self.only_in_second {
+            writeln!(f, "only in target: {}", name)?;
+        }
+
+        for (name, differences) in &self.changed {
+            writeln!(f, "changed: {}", name)?;
+
+            for d in differences {
+                writeln!(f, "    {}", d)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DefinitionComparison {
+    pub source_id: String,
+    pub target_id: String,
+    pub metadata: Option<Vec<FieldDifference>>,
+    pub numeric_sensors: SensorComparison,
+    pub non_numeric_sensors: SensorComparison,
+}
+
+impl fmt::Display for DefinitionComparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "source: {}\ntarget: {}", self.source_id, self.target_id)?;
+
+        if let Some(metadata) = &self.metadata {
+            writeln!(f, "\n---- metadata ----")?;
+
+            if metadata.is_empty() {
+                writeln!(f, "identical")?;
+            }
+
+            for d in metadata {
+                writeln!(f, "{}", d)?;
+            }
+        }
+
+        writeln!(f, "\n---- numeric sensors ----\n{}", self.numeric_sensors)?;
+        write!(
+            f,
+            "---- non-numeric sensors ----\n{}",
+            self.non_numeric_sensors
+        )
+    }
+}
+
+// Compares the serialized fields of two objects, skipping any field listed in ignore
+pub fn compare_fields<T: Serialize>(
+    first: &T,
+    second: &T,
+    ignore: &[&str],
+) -> Result<Vec<FieldDifference>> {
+    let first = serde_json::to_value(first)?;
+    let second = serde_json::to_value(second)?;
+    let mut differences = Vec::new();
+
+    if let (Value::Object(first), Value::Object(second)) = (first, second) {
+        for (field, first_value) in first {
+            if ignore.contains(&field.as_str()) {
+                continue;
+            }
+
+            let second_value = second.get(&field).cloned().unwrap_or(Value::Null);
+
+            if first_value != second_value {
+                differences.push(FieldDifference {
+                    field,
+                    first: first_value,
+                    second: second_value,
+                });
+            }
+        }
+    }
+
+    Ok(differences)
+}
+
+pub fn compare_metadata(first: &Definition, second: &Definition) -> Result<Vec<FieldDifference>> {
+    compare_fields(first, second, &["id"])
+}
+
+// Sensors are matched by name since ids differ between definitions
+pub fn compare_sensor_lists<T: Serialize + GenericSensor>(
+    first: &[T],
+    second: &[T],
+) -> Result<SensorComparison> {
+    let mut comparison = SensorComparison::default();
+
+    for sensor in first {
+        match second.iter().find(|s| s.get_name() == sensor.get_name()) {
+            Some(other) => {
+                let differences = compare_fields(sensor, other, &["id"])?;
+
+                if differences.is_empty() {
+                    comparison.unchanged += 1;
+                } else {
+                    comparison.changed.push((sensor.get_name(), differences));
+                }
+            }
+            None => comparison.only_in_first.push(sensor.get_name()),
+        }
+    }
+
+    for sensor in second {
+        if !first.iter().any(|s| s.get_name() == sensor.get_name()) {
+            comparison.only_in_second.push(sensor.get_name());
+        }
+    }
+
+    Ok(comparison)
+}
+
+fn compare_definition_sensors<T: Serialize + DeserializeOwned + GenericSensor>(
+    config: &AppConfig,
+    definition_type: &DefinitionType,
+    definition_data_type: DefinitionDataType,
+    (source_id, target_id): (&str, &str),
+    auth_header: &str,
+    req: &Client,
+    retry_policy: &RetryPolicy,
+) -> Result<SensorComparison> {
+    let source: Vec<T> = list_sensors(
+        config,
+        definition_type.clone(),
+        definition_data_type.clone(),
+        source_id.to_string(),
+        auth_header.to_string(),
+        req.clone(),
+        retry_policy,
+    )?;
+
+    let target: Vec<T> = list_sensors(
+        config,
+        definition_type.clone(),
+        definition_data_type,
+        target_id.to_string(),
+        auth_header.to_string(),
+        req.clone(),
+        retry_policy,
+    )?;
+
+    compare_sensor_lists(&source, &target)
+}
+
+pub fn compare_definitions(
+    config: &AppConfig,
+    options: &CompareDefinitionsArgs,
+    auth_header: String,
+    req: Client,
+    retry_policy: &RetryPolicy,
+) -> Result<DefinitionComparison> {
+    let definition_type = get_definition_type(&options.definition_type);
+    let source_id = options.source_definition_id.as_str();
+    let target_id = options.target_definition_id.as_str();
+
+    let metadata = if options.include_metadata {
+        let source = get_definition(
+            config,
+            definition_type.clone(),
+            source_id.to_string(),
+            auth_header.clone(),
+            req.clone(),
+            retry_policy,
+        )?;
+        let target = get_definition(
+            config,
+            definition_type.clone(),
+            target_id.to_string(),
+            auth_header.clone(),
+            req.clone(),
+            retry_policy,
+        )?;
+
+        Some(compare_metadata(&source, &target)?)
+    } else {
+        None
+    };
+
+    let (numeric_sensors, non_numeric_sensors) = match definition_type {
+        DefinitionType::Bacnet => (
+            compare_definition_sensors::<BacnetIpNumericSensor>(
+                config,
+                &definition_type,
+                DefinitionDataType::Numeric,
+                (source_id, target_id),
+                &auth_header,
+                &req,
+                retry_policy,
+            )?,
+            compare_definition_sensors::<BacnetIpNonNumericSensor>(
+                config,
+                &definition_type,
+                DefinitionDataType::NonNumeric,
+                (source_id, target_id),
+                &auth_header,
+                &req,
+                retry_policy,
+            )?,
+        ),
+        DefinitionType::Modbus => (
+            compare_definition_sensors::<ModbusTcpNumericSensor>(
+                config,
+                &definition_type,
+                DefinitionDataType::Numeric,
+                (source_id, target_id),
+                &auth_header,
+                &req,
+                retry_policy,
+            )?,
+            compare_definition_sensors::<ModbusTcpNonNumericSensor>(
+                config,
+                &definition_type,
+                DefinitionDataType::NonNumeric,
+                (source_id, target_id),
+                &auth_header,
+                &req,
+                retry_policy,
+            )?,
+        ),
+    };
+
+    Ok(DefinitionComparison {
+        source_id: source_id.to_string(),
+        target_id: target_id.to_string(),
+        metadata,
+        numeric_sensors,
+        non_numeric_sensors,
+    })
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SensorDiff {
+    pub added: Vec<String>,
+    pub updated: Vec<(String, Vec<FieldDifference>)>,
+    pub deleted: Vec<String>,
+    pub unchanged: usize,
+}
+
+impl fmt::Display for SensorDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for name in &self.added {
+            writeln!(f, "add: {}", name)?;
+        }
+
+        for (name, differences) in &self.updated {
+            writeln!(f, "update: {}", name)?;
+
+            for d in differences {
+                writeln!(f, "    {}", d)?;
+            }
+        }
+
+        for name in &self.deleted {
+            writeln!(f, "delete: {}", name)?;
+        }
+
+        write!(
+            f,
+            "{} to add, {} to update, {} to delete, {} unchanged",
+            self.added.len(),
+            self.updated.len(),
+            self.deleted.len(),
+            self.unchanged
+        )
+    }
+}
+
+// Sensors from the CSV are matched to server sensors by id when they have one, otherwise by name.
+// Field differences are reported as server value -> CSV value.
+pub fn diff_sensors<T: Serialize + GenericSensor>(
+    csv_sensors: &[T],
+    server_sensors: &[T],
+) -> Result<SensorDiff> {
+    let mut diff = SensorDiff::default();
+    let mut matched = vec![false; server_sensors.len()];
+
+    for sensor in csv_sensors {
+        let id = sensor.get_id_as_string();
+        let position = if id.is_empty() {
+            server_sensors
+                .iter()
+                .position(|s| s.get_name() == sensor.get_name())
+        } else {
+            server_sensors
+                .iter()
+                .position(|s| s.get_id_as_string() == id)
+        };
+
+        match position {
+            Some(i) => {
+                matched[i] = true;
+                let differences = compare_fields(&server_sensors[i], sensor, &["id"])?;
+
+                if differences.is_empty() {
+                    diff.unchanged += 1;
+                } else {
+                    diff.updated.push((sensor.get_name(), differences));
+                }
+            }
+            None => diff.added.push(sensor.get_name()),
+        }
+    }
+
+    for (sensor, matched) in server_sensors.iter().zip(matched) {
+        if !matched {
+            diff.deleted.push(sensor.get_name());
+        }
+    }
+
+    Ok(diff)
+}
+
+// Unlike a diff, sensors that are only on the server do not fail a verification
+#[derive(Debug, Default, Serialize)]
+pub struct Verification {
+    pub missing: Vec<String>,
+    pub mismatched: Vec<(String, Vec<FieldDifference>)>,
+    pub verified: usize,
+}
+
+impl Verification {
+    pub fn check(&self) -> Result<(), AppError> {
+        if self.missing.is_empty() && self.mismatched.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::VerificationFailed(
+                self.missing.len(),
+                self.mismatched.len(),
+            ))
+        }
+    }
+}
+
+impl From<SensorDiff> for Verification {
+    fn from(diff: SensorDiff) -> Self {
+        Verification {
+            missing: diff.added,
+            mismatched: diff.updated,
+            verified: diff.unchanged,
+        }
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for name in &self.missing {
+            writeln!(f, "missing: {}", name)?;
+        }
+
+        for (name, differences) in &self.mismatched {
+            writeln!(f, "mismatch: {}", name)?;
+
+            for d in differences {
+                writeln!(f, "    {}", d)?;
+            }
+        }
+
+        write!(
+            f,
+            "{} verified, {} missing, {} mismatched",
+            self.verified,
+            self.missing.len(),
+            self.mismatched.len()
+        )
+    }
+}
+
+// template_headers are the columns of a file read with no_header
+fn read_csv_sensors<C, T, F>(
+    options: &DiffArgs,
+    template_headers: &str,
+    separators: &MappingSeparators,
+    clean: F,
+) -> Result<Vec<T>>
+where
+    C: DeserializeOwned,
+    T: FromSensorCsv<C> + GenericSensor,
+    F: Fn(&mut T),
+{
+    let mut reader = open_csv_reader(&options.filename, options.delimiter, !options.no_header)?;
+    let headers = if options.no_header {
+        StringRecord::from(template_headers.split(',').collect::<Vec<_>>())
+    } else {
+        reader.headers()?.clone()
+    };
+    let mut sensors = Vec::new();
+
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |p| p.line());
+        let mut sensor = T::from_csv(record.deserialize::<C>(Some(&headers))?, separators)
+            .map_err(|e| anyhow!("line {}: {}", line, e))?;
+
+        sensor.clean_empty_id();
+        clean(&mut sensor);
+        sensors.push(sensor);
+    }
+
+    Ok(sensors)
+}
+
+fn diff_csv<T: Serialize + DeserializeOwned + GenericSensor>(
+    config: &AppConfig,
+    options: &DiffArgs,
+    csv_sensors: Vec<T>,
+    auth_header: String,
+    req: Client,
+    retry_policy: &RetryPolicy,
+) -> Result<SensorDiff> {
+    let server_sensors: Vec<T> = list_sensors(
+        config,
+        get_definition_type(&options.definition_type),
+        get_definition_data_type(&options.sensor_class),
+        options.definition_id.clone(),
+        auth_header,
+        req,
+        retry_policy,
+    )?;
+
+    diff_sensors(&csv_sensors, &server_sensors)
+}
+
+pub fn diff_definition(
+    config: &AppConfig,
+    options: &DiffArgs,
+    separators: &MappingSeparators,
+    auth_header: String,
+    req: Client,
+    retry_policy: &RetryPolicy,
+) -> Result<SensorDiff> {
+    let definition_type = get_definition_type(&options.definition_type);
+    let data_type = get_definition_data_type(&options.sensor_class);
+    let template_headers = get_template_headers(&definition_type, &data_type)?;
+
+    match (definition_type, data_type) {
+        (DefinitionType::Bacnet, DefinitionDataType::Numeric) => {
+            let sensors = read_csv_sensors::<BacnetIpNumericSensor, BacnetIpNumericSensor, _>(
+                options,
+                &template_headers,
+                separators,
+                |s| s.clean_sensor_empty_unit(),
+            )?;
+            diff_csv(config, options, sensors, auth_header, req, retry_policy)
+        }
+        (DefinitionType::Bacnet, DefinitionDataType::NonNumeric) => {
+            let sensors = read_csv_sensors::<
+                BacnetIpNonNumericSersorCsv,
+                BacnetIpNonNumericSensor,
+                _,
+            >(options, &template_headers, separators, |_| {})?;
+            diff_csv(config, options, sensors, auth_header, req, retry_policy)
+        }
+        (DefinitionType::Modbus, DefinitionDataType::Numeric) => {
+            let sensors = read_csv_sensors::<ModbusTcpNumericSensor, ModbusTcpNumericSensor, _>(
+                options,
+                &template_headers,
+                separators,
+                |s| s.clean_sensor_empty_unit(),
+            )?;
+            diff_csv(config, options, sensors, auth_header, req, retry_policy)
+        }
+        (DefinitionType::Modbus, DefinitionDataType::NonNumeric) => {
+            let sensors = read_csv_sensors::<
+                ModbusTcpNonNumericSensorCsv,
+                ModbusTcpNonNumericSensor,
+                _,
+            >(options, &template_headers, separators, |_| {})?;
+            diff_csv(config, options, sensors, auth_header, req, retry_policy)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_compare_metadata() {
+        let first = Definition {
+            id: Some("5e2b7d1a-51d1-4c5e-9a9f-1d0f4b3f7c11".to_string()),
+            name: "Crah definition".to_string(),
+            asset_type: "Crah".to_string(),
+            associated_assets: 2,
+            ..Default::default()
+        };
+        let second = Definition {
+            id: Some("0b0d5f57-8a5b-4c4d-8f0c-f0b8c9e6a1d2".to_string()),
+            name: "Crah definition".to_string(),
+            asset_type: "Crac".to_string(),
+            associated_assets: 0,
+            ..Default::default()
+        };
+
+        let differences = compare_metadata(&first, &second).unwrap();
+
+        assert_eq!(
+            differences,
+            vec![
+                FieldDifference {
+                    field: "assetType".to_string(),
+                    first: json!("Crah"),
+                    second: json!("Crac"),
+                },
+                FieldDifference {
+                    field: "associatedAssets".to_string(),
+                    first: json!(2),
+                    second: json!(0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_sensor_lists() {
+        let first = vec![
+            BacnetIpNumericSensor {
+                id: Some("13d2cbd0-77c0-49a4-b9c8-38d91ce957d8".to_string()),
+                name: "Supply Temperature".to_string(),
+                ..Default::default()
+            },
+            BacnetIpNumericSensor {
+                name: "Return Temperature".to_string(),
+                unit: Some("Celsius".to_string()),
+                ..Default::default()
+            },
+            BacnetIpNumericSensor {
+                name: "Fan Speed".to_string(),
+                ..Default::default()
+            },
+        ];
+        let second = vec![
+            BacnetIpNumericSensor {
+                id: Some("247a4ad9-9d18-4bf4-b20b-a1d7d61b3971".to_string()),
+                name: "Supply Temperature".to_string(),
+                ..Default::default()
+            },
+            BacnetIpNumericSensor {
+                name: "Return Temperature".to_string(),
+                unit: Some("Fahrenheit".to_string()),
+                ..Default::default()
+            },
+            BacnetIpNumericSensor {
+                name: "Humidity".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let comparison = compare_sensor_lists(&first, &second).unwrap();
+
+        assert_eq!(comparison.unchanged, 1);
+        assert_eq!(comparison.only_in_first, vec!["Fan Speed".to_string()]);
+        assert_eq!(comparison.only_in_second, vec!["Humidity".to_string()]);
+        assert_eq!(comparison.changed.len(), 1);
+        assert_eq!(comparison.changed[0].0, "Return Temperature");
+        assert_eq!(
+            comparison.changed[0].1,
+            vec![FieldDifference {
+                field: "unit".to_string(),
+                first: json!("Celsius"),
+                second: json!("Fahrenheit"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_verification() {
+        let sensor = |id: &str, name: &str, object_instance: usize| BacnetIpNumericSensor {
+            id: Some(id.to_string()).filter(|id| !id.is_empty()),
+            name: name.to_string(),
+            object_instance,
+            ..Default::default()
+        };
+        let server = vec![
+            sensor("0e3f22b9-d0a4-4b69-a2b2-37af4bac7ffb", "Supply Temp", 1),
+            sensor("6a1e5e8e-4a55-4d3c-8b3f-4f1c2a9d7e10", "Return Temp", 2),
+            sensor("9b2c1d4e-5f60-4a7b-8c9d-0e1f2a3b4c5d", "Fan Speed", 3),
+        ];
+
+        // Fan Speed only being on the server is fine
+        let csv = vec![sensor("", "Supply Temp", 1), sensor("", "Return Temp", 2)];
+        let verification = Verification::from(diff_sensors(&csv, &server).unwrap());
+        assert_eq!(verification.verified, 2);
+        assert!(verification.check().is_ok());
+
+        let csv = vec![
+            sensor("", "Supply Temp", 5),
+            sensor("", "Mixed Air Temp", 4),
+        ];
+        let verification = Verification::from(diff_sensors(&csv, &server).unwrap());
+        assert_eq!(verification.missing, vec!["Mixed Air Temp"]);
+        assert_eq!(verification.mismatched[0].0, "Supply Temp");
+        assert_eq!(
+            verification.check().unwrap_err().to_string(),
+            "Verification failed, 1 sensors missing and 1 sensors different"
+        );
+    }
+
+    #[test]
+    fn test_diff_sensors() {
+        let server = vec![
+            BacnetIpNumericSensor {
+                id: Some("13d2cbd0-77c0-49a4-b9c8-38d91ce957d8".to_string()),
+                name: "Supply Temperature".to_string(),
+                ..Default::default()
+            },
+            BacnetIpNumericSensor {
+                id: Some("247a4ad9-9d18-4bf4-b20b-a1d7d61b3971".to_string()),
+                name: "Return Temperature".to_string(),
+                object_instance: 1,
+                ..Default::default()
+            },
+            BacnetIpNumericSensor {
+                id: Some("0b0d5f57-8a5b-4c4d-8f0c-f0b8c9e6a1d2".to_string()),
+                name: "Fan Speed".to_string(),
+                ..Default::default()
+            },
+        ];
+        let csv = vec![
+            // Matched by id even though it was renamed
+            BacnetIpNumericSensor {
+                id: Some("13d2cbd0-77c0-49a4-b9c8-38d91ce957d8".to_string()),
+                name: "Supply Air Temperature".to_string(),
+                ..Default::default()
+            },
+            BacnetIpNumericSensor {
+                name: "Return Temperature".to_string(),
+                object_instance: 1,
+                ..Default::default()
+            },
+            BacnetIpNumericSensor {
+                name: "Humidity".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let diff = diff_sensors(&csv, &server).unwrap();
+
+        assert_eq!(diff.added, vec!["Humidity".to_string()]);
+        assert_eq!(diff.deleted, vec!["Fan Speed".to_string()]);
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(
+            diff.updated,
+            vec![(
+                "Supply Air Temperature".to_string(),
+                vec![FieldDifference {
+                    field: "name".to_string(),
+                    first: json!("Supply Temperature"),
+                    second: json!("Supply Air Temperature"),
+                }]
+            )]
+        );
+    }
+}
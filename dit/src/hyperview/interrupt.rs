@@ -0,0 +1,71 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Once,
+};
+
+// Set by the first Ctrl-C. Imports then stop starting requests, let the ones in flight finish and
+// still write their summary, rejects and report. A second Ctrl-C ends the process right away.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static INSTALL: Once = Once::new();
+
+pub fn install_interrupt_handler() {
+    INSTALL.call_once(imp::install);
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::INTERRUPTED;
+    use std::sync::atomic::Ordering;
+
+    extern "C" fn handle_sigint(_: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+
+        // Only async-signal-safe calls are allowed here
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+
+    pub fn install() {
+        unsafe {
+            libc::signal(
+                libc::SIGINT,
+                handle_sigint as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::INTERRUPTED;
+    use std::sync::atomic::Ordering;
+    use windows_sys::Win32::{
+        Foundation::BOOL,
+        System::Console::{SetConsoleCtrlHandler, CTRL_C_EVENT},
+    };
+
+    // Returning 0 passes the event on to the default handler, which ends the process
+    unsafe extern "system" fn handle_ctrl(ctrl_type: u32) -> BOOL {
+        if ctrl_type == CTRL_C_EVENT && !INTERRUPTED.swap(true, Ordering::SeqCst) {
+            1
+        } else {
+            0
+        }
+    }
+
+    pub fn install() {
+        unsafe {
+            SetConsoleCtrlHandler(Some(handle_ctrl), 1);
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    pub fn install() {}
+}
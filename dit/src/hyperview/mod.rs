@@ -3,3 +3,6 @@ pub mod api_data;
 pub mod app_errors;
 pub mod auth;
 pub mod cli;
+pub mod compare;
+pub mod interrupt;
+pub mod validate;
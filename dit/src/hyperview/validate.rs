@@ -0,0 +1,604 @@
+use anyhow::Result;
+use csv::StringRecord;
+use serde::de::DeserializeOwned;
+use std::{fmt, fs, path::Path};
+use uuid::Uuid;
+
+use super::api_data::{
+    BacnetIpNonNumericSensor, BacnetIpNonNumericSersorCsv, BacnetIpNumericSensor, BacnetSensor,
+    DefinitionDataType, DefinitionType, FromSensorCsv, GenericSensor, MappingSeparators,
+    ModbusSensor, ModbusTcpNonNumericSensor, ModbusTcpNonNumericSensorCsv, ModbusTcpNumericSensor,
+    NumericSensor, SensorType,
+};
+use super::app_errors::AppError;
+use super::cli::{get_template_headers, open_csv_reader};
+
+pub const SENSOR_KINDS: [&str; 4] = [
+    "bacnet-numeric",
+    "bacnet-non-numeric",
+    "modbus-numeric",
+    "modbus-non-numeric",
+];
+
+// How the CSV files are read, like the import options of the same name
+#[derive(Debug, Clone)]
+pub struct ValidateOptions {
+    pub delimiter: u8,
+    pub no_header: bool,
+    pub strict: bool,
+    pub mapping_separators: MappingSeparators,
+}
+
+impl Default for ValidateOptions {
+    fn default() -> Self {
+        ValidateOptions {
+            delimiter: b',',
+            no_header: false,
+            strict: false,
+            mapping_separators: MappingSeparators::default(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RowError {
+    pub line: u64,
+    pub message: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FileValidation {
+    pub filename: String,
+    pub rows: usize,
+    pub errors: Vec<RowError>,
+}
+
+impl FileValidation {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl fmt::Display for FileValidation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = if self.is_valid() { "PASS" } else { "FAIL" };
+
+        write!(
+            f,
+            "{} {} ({} rows, {} errors)",
+            status,
+            self.filename,
+            self.rows,
+            self.errors.len()
+        )?;
+
+        for e in &self.errors {
+            write!(f, "\n    {}", e)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn check_sensor<T: GenericSensor>(sensor: &T) -> Result<(), String> {
+    if sensor.get_name().is_empty() {
+        return Err("name must not be empty".to_string());
+    }
+
+    let id = sensor.get_id_as_string();
+
+    if !id.is_empty() {
+        if let Err(e) = Uuid::try_parse(&id) {
+            return Err(format!("invalid sensor id \"{}\": {}", id, e));
+        }
+    }
+
+    Ok(())
+}
+
+// template_headers are the columns of a file validated with no_header
+fn validate_records<C, F>(
+    filename: &str,
+    template_headers: &str,
+    options: &ValidateOptions,
+    check: F,
+) -> Result<FileValidation>
+where
+    C: DeserializeOwned,
+    F: Fn(C) -> Result<(), String>,
+{
+    let mut reader = open_csv_reader(filename, options.delimiter, !options.no_header)?;
+    let headers = if options.no_header {
+        StringRecord::from(template_headers.split(',').collect::<Vec<_>>())
+    } else {
+        reader.headers()?.clone()
+    };
+    let mut validation = FileValidation {
+        filename: filename.to_string(),
+        ..Default::default()
+    };
+
+    let mut record = StringRecord::new();
+
+    loop {
+        let line = reader.position().line();
+
+        match reader.read_record(&mut record) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => {
+                validation.rows += 1;
+                validation.errors.push(RowError {
+                    line,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        }
+
+        validation.rows += 1;
+
+        let result = record
+            .deserialize::<C>(Some(&headers))
+            .map_err(|e| e.to_string())
+            .and_then(&check);
+
+        if let Err(message) = result {
+            validation.errors.push(RowError { line, message });
+        }
+    }
+
+    Ok(validation)
+}
+
+// The sensor class sensor types of a kind are listed under, numeric or enum
+pub fn get_sensor_class(kind: &str) -> &'static str {
+    if kind.ends_with("non-numeric") {
+        "enum"
+    } else {
+        "numeric"
+    }
+}
+
+// Empty ids are left for the import to resolve, so only ids that are given are checked
+fn check_sensor_type_ids(
+    sensor_types: Option<&[SensorType]>,
+    sensor_type_id: &str,
+    unit_id: Option<&str>,
+) -> Result<(), String> {
+    let Some(sensor_types) = sensor_types else {
+        return Ok(());
+    };
+
+    if sensor_type_id.is_empty() {
+        return Ok(());
+    }
+
+    if !sensor_types
+        .iter()
+        .any(|s| s.sensor_type_id == sensor_type_id)
+    {
+        return Err(format!(
+            "sensor type id \"{}\" is not a sensor type of the asset type",
+            sensor_type_id
+        ));
+    }
+
+    match unit_id.filter(|u| !u.is_empty()) {
+        Some(unit_id)
+            if !sensor_types
+                .iter()
+                .any(|s| s.sensor_type_id == sensor_type_id && s.unit_id == unit_id) =>
+        {
+            Err(format!(
+                "unit id \"{}\" is not a unit of sensor type id \"{}\"",
+                unit_id, sensor_type_id
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+// Validates a sensor CSV without contacting the server. kind is one of SENSOR_KINDS. When sensor
+// types are given, the sensor type and unit ids are checked against them.
+pub fn validate_csv(
+    filename: &str,
+    kind: &str,
+    sensor_types: Option<&[SensorType]>,
+    options: &ValidateOptions,
+) -> Result<FileValidation> {
+    let separators = &options.mapping_separators;
+    let definition_type = if kind.starts_with("modbus") {
+        DefinitionType::Modbus
+    } else {
+        DefinitionType::Bacnet
+    };
+    let data_type = if kind.ends_with("non-numeric") {
+        DefinitionDataType::NonNumeric
+    } else {
+        DefinitionDataType::Numeric
+    };
+    let template_headers = get_template_headers(&definition_type, &data_type)?;
+
+    match kind {
+        "bacnet-numeric" => validate_records(
+            filename,
+            &template_headers,
+            options,
+            |mut sensor: BacnetIpNumericSensor| {
+                check_sensor(&sensor)?;
+                sensor
+                    .check_multiplier_strict(options.strict)
+                    .map_err(|e| e.to_string())?;
+                check_sensor_type_ids(
+                    sensor_types,
+                    &sensor.sensor_type_id,
+                    sensor.unit_id.as_deref(),
+                )?;
+                sensor.normalize_object_type().map_err(|e| e.to_string())
+            },
+        ),
+        "modbus-numeric" => validate_records(
+            filename,
+            &template_headers,
+            options,
+            |mut sensor: ModbusTcpNumericSensor| {
+                check_sensor(&sensor)?;
+                sensor
+                    .check_multiplier_strict(options.strict)
+                    .map_err(|e| e.to_string())?;
+                check_sensor_type_ids(
+                    sensor_types,
+                    &sensor.sensor_type_id,
+                    sensor.unit_id.as_deref(),
+                )?;
+                sensor.normalize_modbus_types().map_err(|e| e.to_string())
+            },
+        ),
+        "bacnet-non-numeric" => validate_records(
+            filename,
+            &template_headers,
+            options,
+            |sensor_csv: BacnetIpNonNumericSersorCsv| {
+                let mut sensor = BacnetIpNonNumericSensor::from_csv(sensor_csv, separators)
+                    .map_err(|e| e.to_string())?;
+                check_sensor(&sensor)?;
+                check_sensor_type_ids(sensor_types, &sensor.sensor_type_id, None)?;
+                sensor.normalize_object_type().map_err(|e| e.to_string())
+            },
+        ),
+        _ => validate_records(
+            filename,
+            &template_headers,
+            options,
+            |sensor_csv: ModbusTcpNonNumericSensorCsv| {
+                let mut sensor = ModbusTcpNonNumericSensor::from_csv(sensor_csv, separators)
+                    .map_err(|e| e.to_string())?;
+                check_sensor(&sensor)?;
+                check_sensor_type_ids(sensor_types, &sensor.sensor_type_id, None)?;
+                sensor.validate_bit_range().map_err(|e| e.to_string())?;
+                sensor.normalize_modbus_types().map_err(|e| e.to_string())
+            },
+        ),
+    }
+}
+
+fn is_csv_file(path: &Path) -> bool {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .is_some_and(|n| n.ends_with(".csv") || n.ends_with(".csv.gz"))
+}
+
+pub fn validate_dir(
+    dir: &str,
+    kind: &str,
+    sensor_types: Option<&[SensorType]>,
+    options: &ValidateOptions,
+) -> Result<Vec<FileValidation>> {
+    let mut filenames = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_csv_file(path))
+        .collect::<Vec<_>>();
+
+    filenames.sort();
+
+    filenames
+        .iter()
+        .map(|path| validate_csv(&path.to_string_lossy(), kind, sensor_types, options))
+        .collect()
+}
+
+// Prints every file result and a total, failing when any file has errors
+pub fn report_validations(validations: &[FileValidation]) -> Result<(), AppError> {
+    let failed = validations.iter().filter(|v| !v.is_valid()).count();
+    let errors: usize = validations.iter().map(|v| v.errors.len()).sum();
+
+    for v in validations {
+        println!("{}", v);
+    }
+
+    println!(
+        "Validated {} files: {} passed, {} failed, {} errors",
+        validations.len(),
+        validations.len() - failed,
+        failed,
+        errors
+    );
+
+    if failed > 0 {
+        return Err(AppError::ValidationFailed);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::{tempdir, NamedTempFile};
+
+    #[test]
+    fn test_validate_csv() {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        write!(
+            tmp_file,
+            r#"id,name,address,dataType,registerType,startBit,endBit,sensorType,sensorTypeId,valueMapping
+,Clogged filter 1,1,uInteger16,holdingRegister,1,16,cloggedFilter,f4531ff2-ebf8-49d2-bd4f-4d64c39e4283,"Inactive:0,Active:1"
+,Clogged filter 2,2,uInteger16,holdingRegisters,1,16,cloggedFilter,f4531ff2-ebf8-49d2-bd4f-4d64c39e4283,"Inactive:0,Active:1"
+,Clogged filter 3,3,uInteger16,holdingRegister,1,16,cloggedFilter,f4531ff2-ebf8-49d2-bd4f-4d64c39e4283,"Inactive:0,Active:x"
+not-a-uuid,Clogged filter 4,4,uInteger16,holdingRegister,1,16,cloggedFilter,f4531ff2-ebf8-49d2-bd4f-4d64c39e4283,"Inactive:0,Active:1"
+,Clogged filter 5,five,uInteger16,holdingRegister,1,16,cloggedFilter,f4531ff2-ebf8-49d2-bd4f-4d64c39e4283,"Inactive:0,Active:1"
+"#
+        )
+        .unwrap();
+
+        let validation = validate_csv(
+            tmp_file.path().to_str().unwrap(),
+            "modbus-non-numeric",
+            None,
+            &ValidateOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(validation.rows, 5);
+        assert!(!validation.is_valid());
+        assert_eq!(
+            validation
+                .errors
+                .iter()
+                .map(|e| e.line)
+                .collect::<Vec<u64>>(),
+            vec![3, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn test_validate_csv_delimiter() {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        write!(
+            tmp_file,
+            "id;name;address;dataType;registerType;startBit;endBit;sensorType;sensorTypeId;valueMapping
+;Clogged filter 1;1;uInteger16;holdingRegister;1;16;cloggedFilter;f4531ff2-ebf8-49d2-bd4f-4d64c39e4283;Inactive:0,Active:1
+"
+        )
+        .unwrap();
+        let filename = tmp_file.path().to_str().unwrap();
+
+        assert!(!validate_csv(
+            filename,
+            "modbus-non-numeric",
+            None,
+            &ValidateOptions::default()
+        )
+        .unwrap()
+        .is_valid());
+
+        let validation = validate_csv(
+            filename,
+            "modbus-non-numeric",
+            None,
+            &ValidateOptions {
+                delimiter: b';',
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(validation.rows, 1);
+        assert!(validation.is_valid());
+    }
+
+    #[test]
+    fn test_validate_csv_no_header() {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        write!(
+            tmp_file,
+            r#",Clogged filter 1,1,uInteger16,holdingRegister,1,16,cloggedFilter,f4531ff2-ebf8-49d2-bd4f-4d64c39e4283,"Inactive:0,Active:1"
+,Clogged filter 2,2,uInteger16,holdingRegister,1,16,cloggedFilter,f4531ff2-ebf8-49d2-bd4f-4d64c39e4283,Unknown
+"#
+        )
+        .unwrap();
+
+        let validation = validate_csv(
+            tmp_file.path().to_str().unwrap(),
+            "modbus-non-numeric",
+            None,
+            &ValidateOptions {
+                no_header: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(validation.rows, 2);
+        assert_eq!(validation.errors.len(), 1);
+        assert_eq!(validation.errors[0].line, 2);
+    }
+
+    #[test]
+    fn test_validate_csv_multiplier() {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        write!(
+            tmp_file,
+            r#"id,name,multiplier,objectInstance,objectType,sensorType,sensorTypeId,unit,unitId
+,Supply Temperature,0.1,1,analogInput,temperature,,,
+,Return Temperature,0,2,analogInput,temperature,,,
+,Fan Speed,NaN,3,analogInput,fanSpeed,,,
+"#
+        )
+        .unwrap();
+
+        let filename = tmp_file.path().to_str().unwrap();
+
+        let validation = validate_csv(
+            filename,
+            "bacnet-numeric",
+            None,
+            &ValidateOptions::default(),
+        )
+        .unwrap();
+        assert!(validation.is_valid());
+
+        let validation = validate_csv(
+            filename,
+            "bacnet-numeric",
+            None,
+            &ValidateOptions {
+                strict: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            validation
+                .errors
+                .iter()
+                .map(|e| e.line)
+                .collect::<Vec<u64>>(),
+            vec![3, 4]
+        );
+    }
+
+    #[test]
+    fn test_validate_csv_sensor_types() {
+        let sensor_types = vec![SensorType {
+            sensor_type_id: "0822ef0a-d0de-4789-9f44-51833c48e7a0".to_string(),
+            unit_id: "16b7b95b-c188-456b-ba53-08c028988cd3".to_string(),
+            ..Default::default()
+        }];
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        write!(
+            tmp_file,
+            r#"id,name,multiplier,objectInstance,objectType,sensorType,sensorTypeId,unit,unitId
+,Cooling Output,1.0,20,analogInput,coolingOutput,0822ef0a-d0de-4789-9f44-51833c48e7a0,Watts,16b7b95b-c188-456b-ba53-08c028988cd3
+,Heating Output,1.0,21,analogInput,heatingOutput,5d0e7d2c-2f0b-4c4f-9a53-8f4d1e3f9b21,Watts,16b7b95b-c188-456b-ba53-08c028988cd3
+,Fan Output,1.0,22,analogInput,fanOutput,0822ef0a-d0de-4789-9f44-51833c48e7a0,Watts,8c1a4b3e-4d6f-4e27-b0c9-2a7d5e9f1c38
+,Damper Output,1.0,23,analogInput,damperOutput,,,
+"#
+        )
+        .unwrap();
+        let filename = tmp_file.path().to_str().unwrap();
+
+        assert!(validate_csv(
+            filename,
+            "bacnet-numeric",
+            None,
+            &ValidateOptions::default()
+        )
+        .unwrap()
+        .is_valid());
+
+        let validation = validate_csv(
+            filename,
+            "bacnet-numeric",
+            Some(&sensor_types),
+            &ValidateOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            validation
+                .errors
+                .iter()
+                .map(|e| e.line)
+                .collect::<Vec<u64>>(),
+            vec![3, 4]
+        );
+    }
+
+    #[test]
+    fn test_validate_csv_extra_columns() {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        write!(
+            tmp_file,
+            r#"id,name,multiplier,objectInstance,objectType,sensorType,sensorTypeId,unit,unitId,notes
+,Cooling Output,1.0,20,analogInput,coolingOutput,0822ef0a-d0de-4789-9f44-51833c48e7a0,Watts,16b7b95b-c188-456b-ba53-08c028988cd3,checked by ops
+,Heating Output,1.0,21,analogInput,heatingOutput,0822ef0a-d0de-4789-9f44-51833c48e7a0,Watts,16b7b95b-c188-456b-ba53-08c028988cd3,
+,Fan Output,1.0,22,analogInput,fanOutput,0822ef0a-d0de-4789-9f44-51833c48e7a0,Watts,16b7b95b-c188-456b-ba53-08c028988cd3,,trailing
+,Damper Output,1.0,23,analogInput
+"#
+        )
+        .unwrap();
+
+        let validation = validate_csv(
+            tmp_file.path().to_str().unwrap(),
+            "bacnet-numeric",
+            None,
+            &ValidateOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(validation.rows, 4);
+        assert_eq!(
+            validation
+                .errors
+                .iter()
+                .map(|e| e.line)
+                .collect::<Vec<u64>>(),
+            vec![5]
+        );
+    }
+
+    #[test]
+    fn test_validate_dir() {
+        let dir = tempdir().unwrap();
+        let mut valid = fs::File::create(dir.path().join("a.csv")).unwrap();
+        write!(
+            valid,
+            "id,name,multiplier,objectInstance,objectType,sensorType,sensorTypeId,unit,unitId\n,Cooling Output,1.0,20,analogInput,coolingOutput,0822ef0a-d0de-4789-9f44-51833c48e7a0,Watts,16b7b95b-c188-456b-ba53-08c028988cd3\n"
+        )
+        .unwrap();
+        let mut invalid = fs::File::create(dir.path().join("b.CSV")).unwrap();
+        write!(
+            invalid,
+            "id,name,multiplier,objectInstance,objectType,sensorType,sensorTypeId,unit,unitId\n,,1.0,20,analogInput,coolingOutput,0822ef0a-d0de-4789-9f44-51833c48e7a0,Watts,16b7b95b-c188-456b-ba53-08c028988cd3\n"
+        )
+        .unwrap();
+        fs::File::create(dir.path().join("notes.txt")).unwrap();
+
+        let validations = validate_dir(
+            dir.path().to_str().unwrap(),
+            "bacnet-numeric",
+            None,
+            &ValidateOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(validations.len(), 2);
+        assert!(validations[0].is_valid());
+        assert_eq!(
+            validations[1].errors,
+            vec![RowError {
+                line: 2,
+                message: "name must not be empty".to_string(),
+            }]
+        );
+    }
+}
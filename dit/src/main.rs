@@ -1,51 +1,264 @@
 use anyhow::Result;
+use chrono::{Local, SecondsFormat};
 use clap::Parser;
-use log::{error, info};
-use std::path::Path;
+use log::{debug, error, info, warn, LevelFilter};
+use std::{
+    io::{stdout, IsTerminal, Write},
+    path::Path,
+    process::ExitCode,
+    time::Instant,
+};
 
 use crate::hyperview::{
     api::{
-        add_definition, import_bacnet_non_numeric_sensors, import_bacnet_numeric_sensors,
-        import_modbus_non_numeric_sensors, import_modbus_numeric_sensors, list_definitions,
-        list_sensor_types, list_sensors,
+        add_definition, copy_sensors, delete_sensors, export_definition, fill_sensor_counts,
+        filter_modified_after, filter_sensors, get_definition, get_request_id, get_sensor_types,
+        get_sensors_url, get_timing_report, import_bacnet_non_numeric_sensors,
+        import_bacnet_numeric_sensors, import_definition_bundle, import_modbus_non_numeric_sensors,
+        import_modbus_numeric_sensors, list_all_sensors, list_asset_types, list_definitions,
+        list_sensor_keys, list_sensors, migrate_definition, new_client_builder,
+        read_cached_sensor_types, read_definition_bundle, read_sensor_ids, resolve_definition_id,
+        select_sensors_by_id, set_record_timings, set_trace_http, test_connection,
+        update_definition, validate_asset_type, DeleteSummary, ImportOptions, RetryPolicy,
     },
     api_data::{
         BacnetIpNonNumericSensor, BacnetIpNonNumericSensorExportWrapper, BacnetIpNumericSensor,
-        DefinitionDataType, DefinitionType, ModbusTcpNonNumericSensor,
-        ModbusTcpNonNumericSensorExportWrapper, ModbusTcpNumericSensor,
+        Definition, DefinitionDataType, DefinitionType, ModbusTcpNonNumericSensor,
+        ModbusTcpNonNumericSensorExportWrapper, ModbusTcpNumericSensor, SensorType,
+        SensorTypeColumns,
     },
-    app_errors::AppError,
-    auth::get_auth_header,
+    app_errors::{get_exit_code, AppError},
+    auth::{get_auth_header, redact_url, TokenInfo},
     cli::{
-        get_config_path, get_debug_filter, handle_output_choice, AppArgs, AppConfig, LoaderCommands,
+        confirm, generate_template, get_config_path, get_debug_filter, get_definition_data_type,
+        get_definition_type, get_json_log_record, handle_all_sensors_output, handle_output,
+        init_config, input_file_exists, load_config, new_spinner, open_csv_reader,
+        report_config_checks, sort_items, AppArgs, AppConfig, LoaderCommands, OutputVerbosity,
     },
+    compare::{compare_definitions, diff_definition, Verification},
+    validate::{get_sensor_class, report_validations, validate_csv, validate_dir, ValidateOptions},
 };
 
 mod hyperview;
 
-fn main() -> Result<()> {
+// Options given on the command line take precedence over the config file
+fn load_profile_config(args: &AppArgs, profile: Option<&str>) -> Result<AppConfig> {
+    let mut config = load_config(get_config_path(profile))?;
+
+    if let Some(ca_cert) = &args.ca_cert {
+        config.ca_cert = Some(ca_cert.clone());
+    }
+    if let Some(timeout_secs) = args.timeout_secs {
+        config.timeout_secs = Some(timeout_secs);
+    }
+    if let Some(page_size) = args.page_size {
+        config.page_size = Some(page_size);
+    }
+    config.danger_accept_invalid_certs = args.danger_accept_invalid_certs;
+    config.refresh_cache = args.refresh_cache;
+    if args.no_token_cache {
+        config.token_cache = Some(false);
+    }
+
+    Ok(config)
+}
+
+fn read_validation_sensor_types(
+    args: &AppArgs,
+    asset_type: &Option<String>,
+    kind: &str,
+) -> Result<Option<Vec<SensorType>>> {
+    match asset_type {
+        Some(asset_type) => {
+            let config = load_profile_config(args, args.profile.as_deref())?;
+            Ok(Some(read_cached_sensor_types(
+                &config,
+                asset_type,
+                get_sensor_class(kind),
+            )?))
+        }
+        None => Ok(None),
+    }
+}
+
+fn print_added_definition(definition: Option<Definition>) {
+    match definition {
+        Some(d) => {
+            println!("Added definition {}\n", d.id.clone().unwrap_or_default());
+            println!("{}", d);
+        }
+        None => println!("Added definition, the server response did not include its id"),
+    }
+}
+
+fn main() -> ExitCode {
     let args = AppArgs::parse();
+    let show_timings = args.timings || args.verbosity() == OutputVerbosity::Verbose;
+    let started = Instant::now();
+    set_record_timings(show_timings);
+
+    let result = run(args);
+
+    if show_timings {
+        eprintln!("{}", get_timing_report(started.elapsed()));
+    }
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::from(get_exit_code(&e))
+        }
+    }
+}
 
-    let debug_level = args.debug_level;
+fn run(args: AppArgs) -> Result<()> {
+    let debug_level = &args.debug_level;
+    let verbosity = args.verbosity();
+    let show_progress =
+        verbosity > OutputVerbosity::Quiet && !args.no_progress && stdout().is_terminal();
 
-    let level_filter = get_debug_filter(&debug_level);
-    env_logger::builder().filter(None, level_filter).init();
+    // Tracing is logged at debug level, so it would not show with the default level
+    let level_filter = match get_debug_filter(debug_level) {
+        l if args.trace_http => l.max(LevelFilter::Debug),
+        l => l,
+    };
+    let mut logger = env_logger::builder();
+    logger.filter(None, level_filter);
+    if args.log_format == "json" {
+        logger.format(|buf, record| {
+            let timestamp = Local::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+            writeln!(buf, "{}", get_json_log_record(record, &timestamp))
+        });
+    }
+    logger.init();
+    set_trace_http(args.trace_http);
+    let mapping_separators = args.mapping_separators()?;
 
     info!("Starting BACnet definition import");
     info!("Startup options:\n| debug level: {} |\n", debug_level);
 
-    let config: AppConfig = confy::load_path(get_config_path())?;
+    if let LoaderCommands::GenerateTemplate(options) = &args.command {
+        return generate_template(options);
+    }
+
+    // Validation is done offline, so it does not need credentials. The configuration is only read
+    // to find cached sensor types.
+    if let LoaderCommands::ValidateDir(options) = &args.command {
+        let sensor_types = read_validation_sensor_types(&args, &options.asset_type, &options.kind)?;
+        return Ok(report_validations(&validate_dir(
+            &options.dir,
+            &options.kind,
+            sensor_types.as_deref(),
+            &ValidateOptions {
+                delimiter: options.delimiter,
+                no_header: options.no_header,
+                strict: options.strict,
+                mapping_separators,
+            },
+        )?)?);
+    }
+
+    if let LoaderCommands::ValidateImport(options) = &args.command {
+        let sensor_types = read_validation_sensor_types(&args, &options.asset_type, &options.kind)?;
+        return Ok(report_validations(&[validate_csv(
+            &options.filename,
+            &options.kind,
+            sensor_types.as_deref(),
+            &ValidateOptions {
+                delimiter: options.delimiter,
+                no_header: options.no_header,
+                strict: options.strict,
+                mapping_separators,
+            },
+        )?])?);
+    }
+
+    if let LoaderCommands::InitConfig(options) = &args.command {
+        return init_config(&get_config_path(args.profile.as_deref()), options.force);
+    }
+
+    if let LoaderCommands::CheckConfig = &args.command {
+        return report_config_checks(
+            &get_config_path(args.profile.as_deref()),
+            load_profile_config(&args, args.profile.as_deref()),
+        );
+    }
+
+    // Everything below talks to the server
+    if verbosity > OutputVerbosity::Quiet {
+        eprintln!("Request id: {}", get_request_id());
+    }
+
+    let retry_policy = RetryPolicy {
+        empty_response_retries: args.empty_response_retries,
+        ..Default::default()
+    };
+
+    // Uses the configurations of two profiles, so it does not load the one of --profile
+    if let LoaderCommands::Migrate(options) = &args.command {
+        let source = load_profile_config(&args, options.source_profile.as_deref())?;
+        let target = load_profile_config(&args, Some(&options.target_profile))?;
+        let (definition, summary) = migrate_definition(&source, &target, options, &retry_policy)?;
+
+        println!(
+            "Added definition {}\n",
+            definition.id.clone().unwrap_or_default()
+        );
+        println!("{}\n", definition);
+        println!("{}", summary);
+
+        return Ok(summary.check()?);
+    }
+
+    let config = load_profile_config(&args, args.profile.as_deref())?;
+
     info!("Hyperview Instance: {}", config.instance_url);
+    debug!("Configuration: {:?}", config);
+
+    // Reports its own authentication failures, so it runs before the token is fetched
+    if let LoaderCommands::TestConnection = &args.command {
+        return test_connection(&config);
+    }
 
     // Get Authorization header for request
     let auth_header = get_auth_header(&config)?;
 
     // Start http client
-    let req = reqwest::blocking::Client::new();
+    let req = new_client_builder(&config)?.build()?;
 
     match &args.command {
-        LoaderCommands::ListBacnetDefinitions => {
-            let resp = list_definitions(&config, DefinitionType::Bacnet, auth_header, req)?;
+        LoaderCommands::ListBacnetDefinitions(options) => {
+            let spinner = new_spinner("Fetching definitions", show_progress);
+            let mut resp = list_definitions(
+                &config,
+                DefinitionType::Bacnet,
+                auth_header.clone(),
+                req.clone(),
+                &retry_policy,
+            )?;
+            resp.retain(|d| options.matches(d));
+            let resp = filter_modified_after(resp, options.modified_after.as_ref());
+
+            if options.count_only {
+                spinner.finish_and_clear();
+                println!("{}", resp.len());
+                return Ok(());
+            }
+
+            let mut resp = sort_items(resp, &options.sort)?;
+
+            if options.with_sensor_counts {
+                fill_sensor_counts(
+                    &config,
+                    &DefinitionType::Bacnet,
+                    &mut resp,
+                    &auth_header,
+                    &req,
+                    &retry_policy,
+                );
+            }
+            spinner.finish_and_clear();
 
             for (i, d) in resp.iter().enumerate() {
                 println!("---- [{}] ----", i);
@@ -61,99 +274,250 @@ fn main() -> Result<()> {
                 DefinitionType::Bacnet,
                 auth_header,
                 req,
+                &retry_policy,
+            )?;
+
+            print_added_definition(resp);
+        }
+
+        LoaderCommands::UpdateBacnetDefinition(options) => {
+            let resp = update_definition(
+                &config,
+                DefinitionType::Bacnet,
+                options,
+                auth_header,
+                req,
+                &retry_policy,
             )?;
 
-            println!("server respone: {}", serde_json::to_string_pretty(&resp)?);
+            if verbosity > OutputVerbosity::Quiet {
+                println!("server response: {}", serde_json::to_string_pretty(&resp)?);
+            }
+        }
+
+        LoaderCommands::ExportBacnetDefinition(options) => {
+            if Path::new(&options.filename).exists() {
+                error!("Specified file already exists. exiting ...");
+                return Err(AppError::FileExists.into());
+            }
+
+            let bundle = export_definition(
+                &config,
+                DefinitionType::Bacnet,
+                &options.definition_id,
+                auth_header,
+                req,
+                &retry_policy,
+            )?;
+
+            std::fs::write(&options.filename, serde_json::to_string_pretty(&bundle)?)?;
+            if verbosity > OutputVerbosity::Quiet {
+                println!(
+                    "Wrote definition with {} numeric and {} non-numeric sensors to {}",
+                    bundle.numeric_sensors.len(),
+                    bundle.non_numeric_sensors.len(),
+                    options.filename
+                );
+            }
         }
 
         LoaderCommands::ListBacnetNumericSensors(options) => {
-            let mut resp: Vec<BacnetIpNumericSensor> = Vec::new();
-            list_sensors(
+            let definition_id = resolve_definition_id(
+                &config,
+                DefinitionType::Bacnet,
+                &options.definition,
+                &auth_header,
+                &req,
+                &retry_policy,
+            )?;
+            let spinner = new_spinner("Fetching sensors", show_progress);
+            let resp: Vec<BacnetIpNumericSensor> = list_sensors(
                 &config,
                 DefinitionType::Bacnet,
                 DefinitionDataType::Numeric,
-                options.definition_id.clone(),
+                definition_id,
                 auth_header,
                 req,
-                &mut resp,
+                &retry_policy,
             )?;
-            let filename = &options.filename;
-            let output_type = &options.output_type;
-
-            handle_output_choice(output_type.to_owned(), filename.to_owned(), resp)?;
+            spinner.finish_and_clear();
+            let resp = filter_modified_after(resp, options.modified_after.as_ref());
+            handle_output(&options.output, resp)?;
         }
 
         LoaderCommands::ListBacnetNonNumericSensors(options) => {
-            let mut resp: Vec<BacnetIpNonNumericSensor> = Vec::new();
-            list_sensors(
+            let definition_id = resolve_definition_id(
+                &config,
+                DefinitionType::Bacnet,
+                &options.definition,
+                &auth_header,
+                &req,
+                &retry_policy,
+            )?;
+            let spinner = new_spinner("Fetching sensors", show_progress);
+            let resp: Vec<BacnetIpNonNumericSensor> = list_sensors(
                 &config,
                 DefinitionType::Bacnet,
                 DefinitionDataType::NonNumeric,
-                options.definition_id.clone(),
+                definition_id,
                 auth_header,
                 req,
-                &mut resp,
+                &retry_policy,
             )?;
+            spinner.finish_and_clear();
+            let resp = filter_modified_after(resp, options.modified_after.as_ref());
             let resp_export_do: Vec<BacnetIpNonNumericSensorExportWrapper> = resp
                 .into_iter()
-                .map(BacnetIpNonNumericSensorExportWrapper)
+                .map(|s| BacnetIpNonNumericSensorExportWrapper(s, mapping_separators))
                 .collect();
-            let filename = &options.filename;
-            let output_type = &options.output_type;
-
-            handle_output_choice(output_type.to_owned(), filename.to_owned(), resp_export_do)?;
+            handle_output(&options.output, resp_export_do)?;
         }
 
         LoaderCommands::ImportBacnetNumericSensors(options) => {
-            let filename = &options.filename;
+            let filename = &options.import.filename;
 
-            if !Path::new(filename).exists() {
+            if !input_file_exists(filename) {
                 error!("Specified input file does not exists. exiting ...");
                 return Err(AppError::InputFileDoesNotExist.into());
             }
 
-            let definition_id = &options.definition_id;
+            let definition_id = &resolve_definition_id(
+                &config,
+                DefinitionType::Bacnet,
+                &options.import.definition,
+                &auth_header,
+                &req,
+                &retry_policy,
+            )?;
 
             info!(
                 "Uploading numeric sensors using file: {}, for definition: {}",
                 filename, definition_id
             );
 
-            import_bacnet_numeric_sensors(
+            let summary = import_bacnet_numeric_sensors(
                 &config,
                 definition_id.to_owned(),
                 filename.to_owned(),
                 auth_header,
                 req,
+                &ImportOptions {
+                    lenient: options.no_validate,
+                    ..options.import.get_import_options(
+                        show_progress,
+                        verbosity,
+                        mapping_separators,
+                    )
+                },
             )?;
+
+            println!("{}", summary);
+            summary.check()?;
         }
 
         LoaderCommands::ImportBacnetNonNumericSensors(options) => {
-            let filename = &options.filename;
+            let filename = &options.import.filename;
 
-            if !Path::new(filename).exists() {
+            if !input_file_exists(filename) {
                 error!("Specified input file does not exists. exiting ...");
                 return Err(AppError::InputFileDoesNotExist.into());
             }
 
-            let definition_id = &options.definition_id;
+            let definition_id = &resolve_definition_id(
+                &config,
+                DefinitionType::Bacnet,
+                &options.import.definition,
+                &auth_header,
+                &req,
+                &retry_policy,
+            )?;
 
             info!(
                 "Uploading numeric sensors using file: {}, for definition: {}",
                 filename, definition_id
             );
 
-            import_bacnet_non_numeric_sensors(
+            let summary = import_bacnet_non_numeric_sensors(
                 &config,
                 definition_id.to_owned(),
                 filename.to_owned(),
                 auth_header,
                 req,
+                &ImportOptions {
+                    lenient: options.no_validate,
+                    ..options.import.get_import_options(
+                        show_progress,
+                        verbosity,
+                        mapping_separators,
+                    )
+                },
             )?;
+
+            println!("{}", summary);
+            summary.check()?;
         }
 
-        LoaderCommands::ListModbusDefinitions => {
-            let resp = list_definitions(&config, DefinitionType::Modbus, auth_header, req)?;
+        LoaderCommands::CopyBacnetNumericSensors(options) => {
+            let summary = copy_sensors::<BacnetIpNumericSensor>(
+                &config,
+                DefinitionType::Bacnet,
+                DefinitionDataType::Numeric,
+                options,
+                auth_header,
+                req,
+                &retry_policy,
+            )?;
+
+            println!("{}", summary);
+            summary.check()?;
+        }
+
+        LoaderCommands::CopyBacnetNonNumericSensors(options) => {
+            let summary = copy_sensors::<BacnetIpNonNumericSensor>(
+                &config,
+                DefinitionType::Bacnet,
+                DefinitionDataType::NonNumeric,
+                options,
+                auth_header,
+                req,
+                &retry_policy,
+            )?;
+
+            println!("{}", summary);
+            summary.check()?;
+        }
+
+        LoaderCommands::ListModbusDefinitions(options) => {
+            let spinner = new_spinner("Fetching definitions", show_progress);
+            let mut resp = list_definitions(
+                &config,
+                DefinitionType::Modbus,
+                auth_header.clone(),
+                req.clone(),
+                &retry_policy,
+            )?;
+            resp.retain(|d| options.matches(d));
+            let resp = filter_modified_after(resp, options.modified_after.as_ref());
+
+            if options.count_only {
+                spinner.finish_and_clear();
+                println!("{}", resp.len());
+                return Ok(());
+            }
+
+            let mut resp = sort_items(resp, &options.sort)?;
+
+            if options.with_sensor_counts {
+                fill_sensor_counts(
+                    &config,
+                    &DefinitionType::Modbus,
+                    &mut resp,
+                    &auth_header,
+                    &req,
+                    &retry_policy,
+                );
+            }
+            spinner.finish_and_clear();
 
             for (i, d) in resp.iter().enumerate() {
                 println!("---- [{}] ----", i);
@@ -169,112 +533,561 @@ fn main() -> Result<()> {
                 DefinitionType::Modbus,
                 auth_header,
                 req,
+                &retry_policy,
+            )?;
+
+            print_added_definition(resp);
+        }
+
+        LoaderCommands::UpdateModbusDefinition(options) => {
+            let resp = update_definition(
+                &config,
+                DefinitionType::Modbus,
+                options,
+                auth_header,
+                req,
+                &retry_policy,
+            )?;
+
+            if verbosity > OutputVerbosity::Quiet {
+                println!("server response: {}", serde_json::to_string_pretty(&resp)?);
+            }
+        }
+
+        LoaderCommands::ExportModbusDefinition(options) => {
+            if Path::new(&options.filename).exists() {
+                error!("Specified file already exists. exiting ...");
+                return Err(AppError::FileExists.into());
+            }
+
+            let bundle = export_definition(
+                &config,
+                DefinitionType::Modbus,
+                &options.definition_id,
+                auth_header,
+                req,
+                &retry_policy,
             )?;
 
-            println!("server respone: {}", serde_json::to_string_pretty(&resp)?);
+            std::fs::write(&options.filename, serde_json::to_string_pretty(&bundle)?)?;
+            if verbosity > OutputVerbosity::Quiet {
+                println!(
+                    "Wrote definition with {} numeric and {} non-numeric sensors to {}",
+                    bundle.numeric_sensors.len(),
+                    bundle.non_numeric_sensors.len(),
+                    options.filename
+                );
+            }
         }
 
         LoaderCommands::ListModbusNumericSensors(options) => {
-            let mut resp: Vec<ModbusTcpNumericSensor> = Vec::new();
-            list_sensors(
+            let definition_id = resolve_definition_id(
+                &config,
+                DefinitionType::Modbus,
+                &options.definition,
+                &auth_header,
+                &req,
+                &retry_policy,
+            )?;
+            let spinner = new_spinner("Fetching sensors", show_progress);
+            let resp: Vec<ModbusTcpNumericSensor> = list_sensors(
                 &config,
                 DefinitionType::Modbus,
                 DefinitionDataType::Numeric,
-                options.definition_id.clone(),
+                definition_id,
                 auth_header,
                 req,
-                &mut resp,
+                &retry_policy,
             )?;
-            let filename = &options.filename;
-            let output_type = &options.output_type;
-
-            handle_output_choice(output_type.to_owned(), filename.to_owned(), resp)?;
+            spinner.finish_and_clear();
+            let resp = filter_modified_after(resp, options.modified_after.as_ref());
+            handle_output(&options.output, resp)?;
         }
 
         LoaderCommands::ListModbusNonNumericSensors(options) => {
-            let mut resp: Vec<ModbusTcpNonNumericSensor> = Vec::new();
-            list_sensors(
+            let definition_id = resolve_definition_id(
+                &config,
+                DefinitionType::Modbus,
+                &options.definition,
+                &auth_header,
+                &req,
+                &retry_policy,
+            )?;
+            let spinner = new_spinner("Fetching sensors", show_progress);
+            let resp: Vec<ModbusTcpNonNumericSensor> = list_sensors(
                 &config,
                 DefinitionType::Modbus,
                 DefinitionDataType::NonNumeric,
-                options.definition_id.clone(),
+                definition_id,
                 auth_header,
                 req,
-                &mut resp,
+                &retry_policy,
             )?;
+            spinner.finish_and_clear();
+            let resp = filter_modified_after(resp, options.modified_after.as_ref());
             let resp_export_do: Vec<ModbusTcpNonNumericSensorExportWrapper> = resp
                 .into_iter()
-                .map(ModbusTcpNonNumericSensorExportWrapper)
+                .map(|s| ModbusTcpNonNumericSensorExportWrapper(s, mapping_separators))
                 .collect();
-            let filename = &options.filename;
-            let output_type = &options.output_type;
-
-            handle_output_choice(output_type.to_owned(), filename.to_owned(), resp_export_do)?;
+            handle_output(&options.output, resp_export_do)?;
         }
 
         LoaderCommands::ImportModbusNumericSensors(options) => {
-            let filename = &options.filename;
+            let filename = &options.import.filename;
 
-            if !Path::new(filename).exists() {
+            if !input_file_exists(filename) {
                 error!("Specified input file does not exists. exiting ...");
                 return Err(AppError::InputFileDoesNotExist.into());
             }
 
-            let definition_id = &options.definition_id;
+            let definition_id = &resolve_definition_id(
+                &config,
+                DefinitionType::Modbus,
+                &options.import.definition,
+                &auth_header,
+                &req,
+                &retry_policy,
+            )?;
 
             info!(
                 "Uploading numeric sensors using file: {}, for definition: {}",
                 filename, definition_id
             );
 
-            import_modbus_numeric_sensors(
+            let summary = import_modbus_numeric_sensors(
                 &config,
                 definition_id.to_owned(),
                 filename.to_owned(),
                 auth_header,
                 req,
+                &ImportOptions {
+                    lenient: options.lenient,
+                    ..options.import.get_import_options(
+                        show_progress,
+                        verbosity,
+                        mapping_separators,
+                    )
+                },
             )?;
+
+            println!("{}", summary);
+            summary.check()?;
         }
 
         LoaderCommands::ImportModbusNonNumericSensors(options) => {
-            let filename = &options.filename;
+            let filename = &options.import.filename;
 
-            if !Path::new(filename).exists() {
+            if !input_file_exists(filename) {
                 error!("Specified input file does not exists. exiting ...");
                 return Err(AppError::InputFileDoesNotExist.into());
             }
 
-            let definition_id = &options.definition_id;
+            let definition_id = &resolve_definition_id(
+                &config,
+                DefinitionType::Modbus,
+                &options.import.definition,
+                &auth_header,
+                &req,
+                &retry_policy,
+            )?;
 
             info!(
                 "Uploading numeric sensors using file: {}, for definition: {}",
                 filename, definition_id
             );
 
-            import_modbus_non_numeric_sensors(
+            let summary = import_modbus_non_numeric_sensors(
                 &config,
                 definition_id.to_owned(),
                 filename.to_owned(),
                 auth_header,
                 req,
+                &ImportOptions {
+                    lenient: options.lenient,
+                    ..options.import.get_import_options(
+                        show_progress,
+                        verbosity,
+                        mapping_separators,
+                    )
+                },
+            )?;
+
+            println!("{}", summary);
+            summary.check()?;
+        }
+
+        LoaderCommands::CopyModbusNumericSensors(options) => {
+            let summary = copy_sensors::<ModbusTcpNumericSensor>(
+                &config,
+                DefinitionType::Modbus,
+                DefinitionDataType::Numeric,
+                options,
+                auth_header,
+                req,
+                &retry_policy,
+            )?;
+
+            println!("{}", summary);
+            summary.check()?;
+        }
+
+        LoaderCommands::CopyModbusNonNumericSensors(options) => {
+            let summary = copy_sensors::<ModbusTcpNonNumericSensor>(
+                &config,
+                DefinitionType::Modbus,
+                DefinitionDataType::NonNumeric,
+                options,
+                auth_header,
+                req,
+                &retry_policy,
+            )?;
+
+            println!("{}", summary);
+            summary.check()?;
+        }
+
+        LoaderCommands::ImportDefinitionBundle(options) => {
+            let bundle = read_definition_bundle(&options.filename)?;
+            let (definition, summary) =
+                import_definition_bundle(&config, &bundle, auth_header, req, &retry_policy)?;
+
+            print_added_definition(Some(definition));
+            println!("{}", summary);
+            summary.check()?;
+        }
+
+        LoaderCommands::SearchSensors(options) => {
+            let spinner = new_spinner("Fetching sensors", show_progress);
+            let definition_type = get_definition_type(&options.definition_type);
+            let definition_data_type = get_definition_data_type(&options.sensor_class);
+
+            match (&definition_type, &definition_data_type) {
+                (DefinitionType::Bacnet, DefinitionDataType::Numeric) => {
+                    let resp: Vec<BacnetIpNumericSensor> = list_sensors(
+                        &config,
+                        definition_type,
+                        definition_data_type,
+                        options.definition_id.clone(),
+                        auth_header,
+                        req,
+                        &retry_policy,
+                    )?;
+                    spinner.finish_and_clear();
+                    let resp = filter_sensors(resp, &options.query, options.match_types);
+                    handle_output(&options.output, resp)?;
+                }
+                (DefinitionType::Bacnet, DefinitionDataType::NonNumeric) => {
+                    let resp: Vec<BacnetIpNonNumericSensor> = list_sensors(
+                        &config,
+                        definition_type,
+                        definition_data_type,
+                        options.definition_id.clone(),
+                        auth_header,
+                        req,
+                        &retry_policy,
+                    )?;
+                    spinner.finish_and_clear();
+                    let resp: Vec<BacnetIpNonNumericSensorExportWrapper> =
+                        filter_sensors(resp, &options.query, options.match_types)
+                            .into_iter()
+                            .map(|s| BacnetIpNonNumericSensorExportWrapper(s, mapping_separators))
+                            .collect();
+                    handle_output(&options.output, resp)?;
+                }
+                (DefinitionType::Modbus, DefinitionDataType::Numeric) => {
+                    let resp: Vec<ModbusTcpNumericSensor> = list_sensors(
+                        &config,
+                        definition_type,
+                        definition_data_type,
+                        options.definition_id.clone(),
+                        auth_header,
+                        req,
+                        &retry_policy,
+                    )?;
+                    spinner.finish_and_clear();
+                    let resp = filter_sensors(resp, &options.query, options.match_types);
+                    handle_output(&options.output, resp)?;
+                }
+                (DefinitionType::Modbus, DefinitionDataType::NonNumeric) => {
+                    let resp: Vec<ModbusTcpNonNumericSensor> = list_sensors(
+                        &config,
+                        definition_type,
+                        definition_data_type,
+                        options.definition_id.clone(),
+                        auth_header,
+                        req,
+                        &retry_policy,
+                    )?;
+                    spinner.finish_and_clear();
+                    let resp: Vec<ModbusTcpNonNumericSensorExportWrapper> =
+                        filter_sensors(resp, &options.query, options.match_types)
+                            .into_iter()
+                            .map(|s| ModbusTcpNonNumericSensorExportWrapper(s, mapping_separators))
+                            .collect();
+                    handle_output(&options.output, resp)?;
+                }
+            }
+        }
+
+        LoaderCommands::ListAllSensors(options) => {
+            let spinner = new_spinner("Fetching sensors", show_progress);
+            let resp = list_all_sensors(
+                &config,
+                get_definition_type(&options.definition_type),
+                options.jobs,
+                mapping_separators,
+                auth_header,
+                req,
+                &retry_policy,
             )?;
+            spinner.finish_and_clear();
+            handle_all_sensors_output(options, &resp)?;
+        }
+
+        LoaderCommands::ListAssetTypes => {
+            let spinner = new_spinner("Fetching asset types", show_progress);
+            let resp = list_asset_types(&config, auth_header, req, &retry_policy)?;
+            spinner.finish_and_clear();
+
+            for asset_type in resp {
+                println!("{}", asset_type);
+            }
+        }
+
+        LoaderCommands::Whoami => {
+            println!("{}", TokenInfo::new(&auth_header, &config.scope));
         }
 
         LoaderCommands::ListSensorTypes(options) => {
-            let query = vec![
-                ("assetTypeId".to_string(), options.asset_type.clone()),
-                (
-                    "sensorTypeValueType".to_string(),
-                    options.sensor_class.clone(),
-                ),
-            ];
+            let asset_type = validate_asset_type(
+                &config,
+                &options.asset_type,
+                &auth_header,
+                &req,
+                &retry_policy,
+            )?;
+
+            let spinner = new_spinner("Fetching sensor types", show_progress);
+            let mut resp = get_sensor_types(
+                &config,
+                &asset_type,
+                &options.sensor_class,
+                auth_header,
+                req,
+                &retry_policy,
+            )?;
+            spinner.finish_and_clear();
+            resp.retain(|t| options.matches(t));
+            handle_output(&options.output, resp)?;
+        }
+
+        LoaderCommands::ListDefinitionSensorTypes(options) => {
+            let definition_type = get_definition_type(&options.definition_type);
+            let definition_id = resolve_definition_id(
+                &config,
+                definition_type.clone(),
+                &options.definition,
+                &auth_header,
+                &req,
+                &retry_policy,
+            )?;
+            let definition = get_definition(
+                &config,
+                definition_type,
+                definition_id,
+                auth_header.clone(),
+                req.clone(),
+                &retry_policy,
+            )?;
+            info!("Definition asset type: {}", definition.asset_type);
+
+            let spinner = new_spinner("Fetching sensor types", show_progress);
+            let mut resp = get_sensor_types(
+                &config,
+                &definition.asset_type,
+                &options.sensor_class,
+                auth_header,
+                req,
+                &retry_policy,
+            )?;
+            spinner.finish_and_clear();
+            resp.retain(|t| options.matches(t));
+            let columns: Vec<SensorTypeColumns> = resp.into_iter().map(Into::into).collect();
+            handle_output(&options.output, columns)?;
+        }
+
+        LoaderCommands::CompareDefinitions(options) => {
+            let report = compare_definitions(&config, options, auth_header, req, &retry_policy)?;
+
+            println!("{}", report);
+        }
 
-            let resp = list_sensor_types(&config, query, auth_header, req)?;
-            let filename = &options.filename;
-            let output_type = &options.output_type;
+        LoaderCommands::Diff(options) => {
+            let diff = diff_definition(
+                &config,
+                options,
+                &mapping_separators,
+                auth_header,
+                req,
+                &retry_policy,
+            )?;
 
-            handle_output_choice(output_type.to_owned(), filename.to_owned(), resp)?;
+            if options.output == "json" {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                println!("{}", diff);
+            }
         }
+
+        LoaderCommands::ClearSensors(options) => {
+            let definition_type = get_definition_type(&options.definition_type);
+            let definition_data_type = get_definition_data_type(&options.sensor_class);
+            let sensors_url = get_sensors_url(
+                &config,
+                &definition_type,
+                &definition_data_type,
+                &options.definition_id,
+            );
+            let sensors = list_sensor_keys(
+                &config,
+                definition_type,
+                definition_data_type,
+                options.definition_id.clone(),
+                auth_header.clone(),
+                req.clone(),
+                &retry_policy,
+            )?;
+
+            if sensors.is_empty() {
+                println!("Definition has no {} sensors", options.sensor_class);
+            } else if options.dry_run {
+                println!("target: {}", redact_url(&sensors_url));
+                for (id, name) in &sensors {
+                    println!("delete: {} ({})", name, id);
+                }
+                println!("{} sensors would be deleted", sensors.len());
+            } else if options.yes
+                || confirm(&format!(
+                    "Delete {} {} sensors from definition {}?",
+                    sensors.len(),
+                    options.sensor_class,
+                    options.definition_id
+                ))?
+            {
+                let summary = delete_sensors(
+                    &sensors_url,
+                    sensors,
+                    &auth_header,
+                    &req,
+                    options.jobs,
+                    show_progress,
+                    &retry_policy,
+                );
+                println!("{}", summary);
+                summary.check()?;
+            } else {
+                println!("Nothing deleted");
+            }
+        }
+
+        LoaderCommands::DeleteSensors(options) => {
+            if !input_file_exists(&options.ids_file) {
+                error!("Specified input file does not exists. exiting ...");
+                return Err(AppError::InputFileDoesNotExist.into());
+            }
+
+            let id_list = read_sensor_ids(&mut open_csv_reader(&options.ids_file, b',', false)?)?;
+            for (line, value) in &id_list.invalid {
+                error!("Skipping line {}: invalid sensor id \"{}\"", line, value);
+            }
+
+            let definition_type = get_definition_type(&options.definition_type);
+            let definition_data_type = get_definition_data_type(&options.sensor_class);
+            let sensors_url = get_sensors_url(
+                &config,
+                &definition_type,
+                &definition_data_type,
+                &options.definition_id,
+            );
+            let sensor_keys = list_sensor_keys(
+                &config,
+                definition_type,
+                definition_data_type,
+                options.definition_id.clone(),
+                auth_header.clone(),
+                req.clone(),
+                &retry_policy,
+            )?;
+            let (sensors, missing) = select_sensors_by_id(sensor_keys, &id_list.ids);
+            for id in &missing {
+                warn!("Skipping sensor {}: not on the definition", id);
+            }
+            let skipped = id_list.invalid.len() + missing.len();
+
+            if options.dry_run {
+                println!("target: {}", redact_url(&sensors_url));
+                for (id, name) in &sensors {
+                    println!("delete: {} ({})", name, id);
+                }
+                println!(
+                    "{} sensors would be deleted, {} skipped",
+                    sensors.len(),
+                    skipped
+                );
+            } else if sensors.is_empty()
+                || options.yes
+                || confirm(&format!(
+                    "Delete {} {} sensors from definition {}?",
+                    sensors.len(),
+                    options.sensor_class,
+                    options.definition_id
+                ))?
+            {
+                let summary = DeleteSummary {
+                    skipped,
+                    ..delete_sensors(
+                        &sensors_url,
+                        sensors,
+                        &auth_header,
+                        &req,
+                        options.jobs,
+                        show_progress,
+                        &retry_policy,
+                    )
+                };
+                println!("{}", summary);
+                summary.check()?;
+            } else {
+                println!("Nothing deleted");
+            }
+        }
+
+        LoaderCommands::Verify(options) => {
+            let verification = Verification::from(diff_definition(
+                &config,
+                options,
+                &mapping_separators,
+                auth_header,
+                req,
+                &retry_policy,
+            )?);
+
+            if options.output == "json" {
+                println!("{}", serde_json::to_string_pretty(&verification)?);
+            } else {
+                println!("{}", verification);
+            }
+
+            verification.check()?;
+        }
+
+        LoaderCommands::ValidateDir(_)
+        | LoaderCommands::ValidateImport(_)
+        | LoaderCommands::TestConnection
+        | LoaderCommands::InitConfig(_)
+        | LoaderCommands::CheckConfig
+        | LoaderCommands::Migrate(_)
+        | LoaderCommands::GenerateTemplate(_) => unreachable!(),
     }
 
     Ok(())